@@ -8,19 +8,38 @@ use std::{
 
 mod app;
 mod arguments;
+mod config;
+mod crash;
+mod game_log;
+mod history;
 mod install;
+mod instance;
+mod jvm;
+mod known_issues;
 mod launch;
+mod library_overrides;
+mod log4shell;
 mod manifest;
 mod modding;
+mod monitor;
 mod path;
+mod profiles;
 mod rendering;
+mod runtime;
+mod session;
 mod utils;
+mod validation;
 
 use manifest::version;
 use std::path::Path;
 
 pub fn main() {
-    minecraft_folder();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.get(0).map(|arg| arg.as_str()) {
+        Some("runtimes") => runtime::run_command(&args[1..]),
+        _ => minecraft_folder(),
+    }
 }
 
 fn minecraft_folder() {
@@ -43,6 +62,17 @@ fn minecraft_folder() {
         }
     };
 
+    // Write the config back so that new options show up in the file, a file we couldn't
+    // understand is left alone so a typo doesn't wipe the user's settings.
+    match config::read_config() {
+        Ok(config) => {
+            if let Err(err) = config::write_config(&config) {
+                println!("{}", err);
+            }
+        }
+        Err(err) => println!("{}, using defaults.", err),
+    };
+
     let version_folder =
         match path::get_or_create_dir(&minecraft_folder, "versions".parse().unwrap()) {
             Some(p) => p,
@@ -61,10 +91,17 @@ fn minecraft_folder() {
         println!("Version {} of type {}", version.id, version._type);
         installed_id.push(version.id.clone());
     }
+
+    if let Err(err) = profiles::update_launcher_profiles(&installed) {
+        println!("Failed to update launcher profiles: {}", err);
+    }
+
     let mut all_versions: Vec<manifest::main::MinVersion> = Vec::new();
 
+    // Versions inheriting from another one were added by a mod loader installer (ours or
+    // an external one like Forge or OptiFine) and can be launched directly.
     for version in &installed {
-        if !version.is_modded() {
+        if !version.is_modded() || version.inherits_from.is_some() {
             all_versions.push(version.to_min_version());
         }
     }
@@ -9,6 +9,7 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use std::collections::HashMap;
 use std::error::Error;
 use std::io::Stdout;
 use std::sync::mpsc;
@@ -20,12 +21,18 @@ use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Style};
 use tui::text::Span;
 use tui::text::Spans;
-use tui::widgets::{Block, Borders, Paragraph, Tabs, Wrap};
+use tui::widgets::{Block, Borders, Clear, Paragraph, Tabs, Wrap};
 use tui::{Frame, Terminal};
 
+// Worlds can take a while to save, games still going after this are left running.
+const QUIT_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub mod download_tab;
+mod instance_tab;
 mod launch_tab;
 mod login_tab;
+mod profile_tab;
+mod runtime_tab;
 mod version_tab;
 
 pub struct App {
@@ -33,11 +40,20 @@ pub struct App {
     pub version_tab: version_tab::VersionTab,
     pub download_tab: download_tab::DownloadTab,
     pub launch_tab: launch_tab::GameLogTab,
+    pub runtime_tab: runtime_tab::RuntimeTab,
+    pub profile_tab: profile_tab::ProfileTab,
+    pub instance_tab: instance_tab::InstanceTab,
     pub current_tab: Tab,
+    // Asked when quitting while games are running.
+    pub quit_prompt: bool,
+    // Set once the games were asked to close, the launcher waits for them to exit.
+    quitting: Option<Instant>,
 }
 
 impl App {
     pub fn new(min_versions: Vec<MinVersion>, versions: Vec<Version>) -> App {
+        let instance_tab = instance_tab::InstanceTab::new(min_versions.clone(), versions.clone());
+        let launch_tab = launch_tab::GameLogTab::new(min_versions.clone(), versions.clone());
         let mut app = App {
             login_tab: login_tab::LoginTab::new(),
             version_tab: version_tab::VersionTab {
@@ -52,10 +68,18 @@ impl App {
                 loader_version_list: StatefulList::new(),
                 versions,
                 modding_handler: ModLoaderHandler::new(),
+                playtimes: HashMap::new(),
+                recent: vec![],
+                message: String::new(),
             },
             download_tab: download_tab::DownloadTab::new(),
-            launch_tab: launch_tab::GameLogTab::new(),
+            launch_tab,
+            runtime_tab: runtime_tab::RuntimeTab::new(),
+            profile_tab: profile_tab::ProfileTab::new(),
+            instance_tab,
             current_tab: Tab::Login,
+            quit_prompt: false,
+            quitting: None,
         };
         app.version_tab.build_table_state();
         app
@@ -70,8 +94,11 @@ impl App {
         match self.current_tab {
             Tab::Login => self.login_tab.render(f, chunks[0]),
             Tab::Version => self.version_tab.render(f, chunks[0]),
-            Tab::Download(_, _, _, _) => self.download_tab.render(f, chunks[0]),
-            Tab::Launch(_) => self.launch_tab.render(f, chunks[0]),
+            Tab::Download(_, _, _, _, _, _) => self.download_tab.render(f, chunks[0]),
+            Tab::Launch(_, _, _) => self.launch_tab.render(f, chunks[0]),
+            Tab::Runtime => self.runtime_tab.render(f, chunks[0]),
+            Tab::Profile(_) => self.profile_tab.render(f, chunks[0]),
+            Tab::Instance => self.instance_tab.render(f, chunks[0]),
             Tab::Mod => {}
             Tab::ModVersion => {}
         };
@@ -109,12 +136,78 @@ impl App {
             .alignment(Alignment::Left)
             .wrap(Wrap { trim: true });
 
-        f.render_widget(bindings_paragraph, chunks[1])
+        f.render_widget(bindings_paragraph, chunks[1]);
+
+        if self.quit_prompt {
+            self.render_quit_prompt(f, area);
+        }
+    }
+
+    fn render_quit_prompt(&self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let prompt_area = Rect::new(
+            area.x + area.width.saturating_sub(50) / 2,
+            area.y + area.height.saturating_sub(7) / 2,
+            area.width.min(50),
+            area.height.min(7),
+        );
+
+        let lines = match self.quitting {
+            Some(started) => vec![
+                Spans::from(format!(
+                    "Waiting for {} game(s) to exit...",
+                    self.launch_tab.get_active_count()
+                )),
+                Spans::from(""),
+                Spans::from(format!(
+                    "Quitting anyway in {}s",
+                    QUIT_TIMEOUT.saturating_sub(started.elapsed()).as_secs()
+                )),
+                Spans::from("K: Kill them"),
+            ],
+            None => vec![
+                Spans::from(format!(
+                    "{} game(s) still running.",
+                    self.launch_tab.get_running_count()
+                )),
+                Spans::from(""),
+                Spans::from("S: Stop them and quit    K: Kill them and quit"),
+                Spans::from("D: Quit and leave them running"),
+                Spans::from("ESC: Cancel"),
+            ],
+        };
+
+        let prompt = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Quit")
+                    .border_style(Style::default().fg(Color::Yellow)),
+            )
+            .alignment(Alignment::Center);
+
+        f.render_widget(Clear, prompt_area);
+        f.render_widget(prompt, prompt_area);
     }
 
     fn get_bindings(&mut self) -> Vec<TabBinding> {
         let mut vec = Vec::new();
 
+        if self.quitting.is_some() {
+            return vec![TabBinding::Default(
+                String::from("K"),
+                String::from("Kill the games"),
+            )];
+        }
+
+        if self.quit_prompt {
+            return vec![
+                TabBinding::Default(String::from("S"), String::from("Stop the games and quit")),
+                TabBinding::Default(String::from("K"), String::from("Kill the games and quit")),
+                TabBinding::Default(String::from("D"), String::from("Quit, games keep running")),
+                TabBinding::Default(String::from("ESC"), String::from("Cancel")),
+            ];
+        }
+
         vec.push(TabBinding::Default(
             String::from("ESC"),
             String::from("Quit App"),
@@ -133,18 +226,36 @@ impl App {
                     vec.push(tab_binding);
                 }
             }
-            Tab::Download(_, _, _, _) => {
+            Tab::Download(_, _, _, _, _, _) => {
                 let tab_vec = self.download_tab.get_bindings();
                 for tab_binding in tab_vec {
                     vec.push(tab_binding);
                 }
             }
-            Tab::Launch(_) => {
+            Tab::Launch(_, _, _) => {
                 let tab_vec = self.launch_tab.get_bindings();
                 for tab_binding in tab_vec {
                     vec.push(tab_binding);
                 }
             }
+            Tab::Runtime => {
+                let tab_vec = self.runtime_tab.get_bindings();
+                for tab_binding in tab_vec {
+                    vec.push(tab_binding);
+                }
+            }
+            Tab::Profile(_) => {
+                let tab_vec = self.profile_tab.get_bindings();
+                for tab_binding in tab_vec {
+                    vec.push(tab_binding);
+                }
+            }
+            Tab::Instance => {
+                let tab_vec = self.instance_tab.get_bindings();
+                for tab_binding in tab_vec {
+                    vec.push(tab_binding);
+                }
+            }
             Tab::Mod => {}
             Tab::ModVersion => {}
         }
@@ -153,11 +264,17 @@ impl App {
     }
 
     fn tick(&mut self) -> Action {
+        // Games keep running and logging while other tabs are shown.
+        self.launch_tab.poll_sessions();
+
         match self.current_tab {
             Tab::Login => self.login_tab.tick(),
             Tab::Version => self.version_tab.tick(),
-            Tab::Download(_, _, _, _) => self.download_tab.tick(),
-            Tab::Launch(_) => self.launch_tab.tick(),
+            Tab::Download(_, _, _, _, _, _) => self.download_tab.tick(),
+            Tab::Launch(_, _, _) => self.launch_tab.tick(),
+            Tab::Runtime => self.runtime_tab.tick(),
+            Tab::Profile(_) => self.profile_tab.tick(),
+            Tab::Instance => self.instance_tab.tick(),
             Tab::Mod => Action::None,
             Tab::ModVersion => Action::None,
         }
@@ -220,10 +337,13 @@ impl App {
             let selected_tab = match self.current_tab.clone() {
                 Tab::Login => 0,
                 Tab::Version => 1,
-                Tab::Download(_, _, _, _) => 2,
-                Tab::Launch(_) => 3,
-                Tab::Mod => 4,
-                Tab::ModVersion => 5,
+                Tab::Download(_, _, _, _, _, _) => 2,
+                Tab::Launch(_, _, _) => 3,
+                Tab::Runtime => 4,
+                Tab::Profile(_) => 5,
+                Tab::Instance => 6,
+                Tab::Mod => 7,
+                Tab::ModVersion => 8,
             };
             terminal.draw(|f| {
                 let main_chunks = Layout::default()
@@ -240,6 +360,9 @@ impl App {
                     Spans::from("Version"),
                     Spans::from("Installation"),
                     Spans::from("Launch"),
+                    Spans::from("Runtimes"),
+                    Spans::from("Profile"),
+                    Spans::from("Instances"),
                 ]);
 
                 let tabs = Tabs::new(ve)
@@ -255,7 +378,65 @@ impl App {
             })?;
 
             match rx.recv()? {
+                // Games that don't stop in time can still be killed.
+                Event::Input(key) if self.quitting.is_some() => {
+                    if key.code == KeyCode::Char('k') {
+                        self.launch_tab.kill_all();
+                    }
+                }
+                // Polling is what records the exit and runs the post-exit commands.
+                Event::Tick if self.quitting.is_some() => {
+                    self.launch_tab.poll_sessions();
+
+                    let timed_out = self
+                        .quitting
+                        .map_or(false, |started| started.elapsed() >= QUIT_TIMEOUT);
+                    if self.launch_tab.get_active_count() == 0 || timed_out {
+                        disable_raw_mode()?;
+                        execute!(
+                            terminal.backend_mut(),
+                            LeaveAlternateScreen,
+                            DisableMouseCapture
+                        )?;
+                        terminal.show_cursor()?;
+                        break;
+                    }
+                }
+                Event::Input(key) if self.quit_prompt => {
+                    let quit = match key.code {
+                        KeyCode::Char('s') => {
+                            self.launch_tab.stop_all();
+                            self.quitting = Some(Instant::now());
+                            false
+                        }
+                        KeyCode::Char('k') => {
+                            self.launch_tab.kill_all();
+                            self.quitting = Some(Instant::now());
+                            false
+                        }
+                        KeyCode::Char('d') => true,
+                        KeyCode::Esc => {
+                            self.quit_prompt = false;
+                            false
+                        }
+                        _ => false,
+                    };
+
+                    if quit {
+                        disable_raw_mode()?;
+                        execute!(
+                            terminal.backend_mut(),
+                            LeaveAlternateScreen,
+                            DisableMouseCapture
+                        )?;
+                        terminal.show_cursor()?;
+                        break;
+                    }
+                }
                 Event::Input(key) => match key.code {
+                    KeyCode::Esc if self.launch_tab.get_running_count() > 0 => {
+                        self.quit_prompt = true;
+                    }
                     KeyCode::Esc => {
                         disable_raw_mode()?;
                         execute!(
@@ -273,17 +454,22 @@ impl App {
                                 self.current_tab = tab.clone();
                                 match tab {
                                     Tab::Login => {}
-                                    Tab::Version => {}
-                                    Tab::Download(v, ref vs, l, lv) => {
-                                        self.download_tab.start(v, vs.clone(), l, lv)
+                                    Tab::Version => self.version_tab.refresh_history(),
+                                    Tab::Download(v, ref vs, l, lv, p, mode) => {
+                                        self.download_tab.start(v, vs.clone(), l, lv, p, mode)
                                     }
-                                    Tab::Launch(version) => self.launch_tab.init(
+                                    Tab::Launch(version, p, mode) => self.launch_tab.init(
                                         &version,
-                                        self.login_tab.name.clone(),
-                                        self.login_tab.uuid.clone().to_string(),
-                                        self.login_tab.token.clone(),
-                                        self.login_tab.user_type.clone(),
+                                        p,
+                                        mode,
+                                        self.download_tab.get_target(),
+                                        self.login_tab.get_account(),
                                     ),
+                                    Tab::Runtime => self.runtime_tab.init(),
+                                    Tab::Profile(ref version_id) => {
+                                        self.profile_tab.init(version_id)
+                                    }
+                                    Tab::Instance => self.instance_tab.init(),
                                     Tab::Mod => {}
                                     Tab::ModVersion => {}
                                 }
@@ -297,17 +483,20 @@ impl App {
                         self.current_tab = tab.clone();
                         match tab {
                             Tab::Login => {}
-                            Tab::Version => {}
-                            Tab::Download(v, ref vs, l, lv) => {
-                                self.download_tab.start(v, vs.clone(), l, lv)
+                            Tab::Version => self.version_tab.refresh_history(),
+                            Tab::Download(v, ref vs, l, lv, p, mode) => {
+                                self.download_tab.start(v, vs.clone(), l, lv, p, mode)
                             }
-                            Tab::Launch(version) => self.launch_tab.init(
+                            Tab::Launch(version, p, mode) => self.launch_tab.init(
                                 &version,
-                                self.login_tab.name.clone(),
-                                self.login_tab.uuid.clone().to_string(),
-                                self.login_tab.token.clone(),
-                                self.login_tab.user_type.clone(),
+                                p,
+                                mode,
+                                self.download_tab.get_target(),
+                                self.login_tab.get_account(),
                             ),
+                            Tab::Runtime => self.runtime_tab.init(),
+                            Tab::Profile(ref version_id) => self.profile_tab.init(version_id),
+                            Tab::Instance => self.instance_tab.init(),
                             Tab::Mod => {}
                             Tab::ModVersion => {}
                         }
@@ -323,8 +512,11 @@ impl App {
         match self.current_tab {
             Tab::Login => self.login_tab.on_key_press(key_code),
             Tab::Version => self.version_tab.on_key_press(key_code),
-            Tab::Download(_, _, _, _) => self.download_tab.on_key_press(key_code),
-            Tab::Launch(_) => self.launch_tab.on_key_press(key_code),
+            Tab::Download(_, _, _, _, _, _) => self.download_tab.on_key_press(key_code),
+            Tab::Launch(_, _, _) => self.launch_tab.on_key_press(key_code),
+            Tab::Runtime => self.runtime_tab.on_key_press(key_code),
+            Tab::Profile(_) => self.profile_tab.on_key_press(key_code),
+            Tab::Instance => self.instance_tab.on_key_press(key_code),
             Tab::Mod => Action::None,
             Tab::ModVersion => Action::None,
         }
@@ -344,8 +536,13 @@ pub enum Tab {
         Vec<Version>,
         Box<dyn ModLoaderInstaller>,
         Option<String>,
+        Option<String>,
+        LaunchMode,
     ),
-    Launch(version::Main),
+    Launch(version::Main, Option<String>, LaunchMode),
+    Runtime,
+    Profile(String),
+    Instance,
     Mod,
     ModVersion,
 }
@@ -355,16 +552,32 @@ impl Tab {
         match self {
             Tab::Login => Tab::Login,
             Tab::Version => Tab::Version,
-            Tab::Download(v, vs, l, lv) => {
-                Tab::Download(v.clone(), vs.clone(), l.clone_instance(), lv.clone())
-            }
-            Tab::Launch(v) => Tab::Launch(v.clone()),
+            Tab::Download(v, vs, l, lv, p, m) => Tab::Download(
+                v.clone(),
+                vs.clone(),
+                l.clone_instance(),
+                lv.clone(),
+                p.clone(),
+                *m,
+            ),
+            Tab::Launch(v, p, m) => Tab::Launch(v.clone(), p.clone(), *m),
+            Tab::Runtime => Tab::Runtime,
+            Tab::Profile(v) => Tab::Profile(v.clone()),
+            Tab::Instance => Tab::Instance,
             Tab::Mod => Tab::Mod,
             Tab::ModVersion => Tab::ModVersion,
         }
     }
 }
 
+/// What the launch tab does with an installed version.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LaunchMode {
+    Play,
+    // Writes the launch script instead of starting the game, with the access token or not.
+    Export(bool),
+}
+
 pub enum TabBinding {
     Default(String, String),
     Enablable(String, String, bool),
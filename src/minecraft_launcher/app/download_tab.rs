@@ -1,5 +1,8 @@
-use crate::minecraft_launcher::app::{Action, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::app::{Action, LaunchMode, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::history::LaunchTarget;
 use crate::minecraft_launcher::install;
+use crate::minecraft_launcher::library_overrides::LibraryOverrides;
+use crate::minecraft_launcher::{instance, profiles};
 
 use crate::minecraft_launcher::manifest::main::{MinVersion, Version};
 use crate::minecraft_launcher::manifest::version;
@@ -26,6 +29,9 @@ pub struct DownloadTab {
     current_sub_sub_step: Option<(String, u64, u64)>,
     error: Option<String>,
     installed: Option<version::Main>,
+    profile_id: Option<String>,
+    target: Option<LaunchTarget>,
+    mode: LaunchMode,
 }
 
 impl DownloadTab {
@@ -37,6 +43,9 @@ impl DownloadTab {
             current_sub_sub_step: None,
             error: None,
             installed: None,
+            profile_id: None,
+            target: None,
+            mode: LaunchMode::Play,
         }
     }
 
@@ -46,8 +55,25 @@ impl DownloadTab {
         versions: Vec<Version>,
         loader: Box<dyn ModLoaderInstaller>,
         loader_version: Option<String>,
+        profile_id: Option<String>,
+        mode: LaunchMode,
     ) {
         let (tx, rx) = mpsc::channel();
+        // A previous install must not be launched again by tick.
+        *self = DownloadTab::new();
+        // Instances can replace libraries with a library_overrides.json in their folder.
+        let instance_dir = profile_id
+            .as_deref()
+            .and_then(|profile_id| profiles::get_version_profile(profile_id).1.rusty_craft)
+            .and_then(|settings| settings.instance)
+            .map(|name| instance::get_instances_folder().join(name));
+        self.profile_id = profile_id;
+        self.mode = mode;
+        self.target = Some(LaunchTarget {
+            minecraft_version: version.id.clone(),
+            loader: loader.get_name(),
+            loader_version: loader_version.clone(),
+        });
 
         let modded_version = if !loader.is_vanilla() {
             match loader.create_profile(version.id.clone(), match loader_version {
@@ -64,7 +90,20 @@ impl DownloadTab {
         thread::spawn(move || {
             tx.send(Message::Init)
                 .expect("Cannot send message to receiver!");
-            match install::install_version(version.clone().id, versions, tx, modded_version) {
+            let overrides = match LibraryOverrides::load(instance_dir.as_deref()) {
+                Ok(overrides) => overrides,
+                Err(err) => {
+                    tx.send(Message::Error(err)).unwrap_or(());
+                    return;
+                }
+            };
+            match install::install_version(
+                version.clone().id,
+                versions,
+                tx,
+                modded_version,
+                &overrides,
+            ) {
                 None => {
                     // panic!("Failed to install version {}", version.id)
                 }
@@ -76,6 +115,10 @@ impl DownloadTab {
 
         self.rx = Some(rx);
     }
+
+    pub fn get_target(&self) -> Option<LaunchTarget> {
+        self.target.clone()
+    }
 }
 
 impl TabTrait for DownloadTab {
@@ -218,7 +261,9 @@ impl TabTrait for DownloadTab {
     fn tick(&mut self) -> Action {
         match self.installed.clone() {
             None => Action::None,
-            Some(version) => Action::NextTab(Tab::Launch(version)),
+            Some(version) => {
+                Action::NextTab(Tab::Launch(version, self.profile_id.clone(), self.mode))
+            }
         }
     }
 
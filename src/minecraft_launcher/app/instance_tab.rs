@@ -0,0 +1,265 @@
+use crate::minecraft_launcher::app::{Action, LaunchMode, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::instance;
+use crate::minecraft_launcher::instance::Instance;
+use crate::minecraft_launcher::manifest::main::{MinVersion, Version};
+use crate::minecraft_launcher::modding::ModLoaderHandler;
+use crate::minecraft_launcher::rendering::utils::StatefulTable;
+use crossterm::event::KeyCode;
+
+use std::io::Stdout;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap};
+use tui::Frame;
+
+pub struct InstanceTab {
+    all_versions: Vec<MinVersion>,
+    versions: Vec<Version>,
+    modding_handler: ModLoaderHandler,
+    instance_table: StatefulTable<Instance>,
+    renaming: Option<String>,
+    confirm_delete: bool,
+    message: String,
+}
+
+impl InstanceTab {
+    pub fn new(all_versions: Vec<MinVersion>, versions: Vec<Version>) -> InstanceTab {
+        InstanceTab {
+            all_versions,
+            versions,
+            modding_handler: ModLoaderHandler::new(),
+            instance_table: StatefulTable::new(),
+            renaming: None,
+            confirm_delete: false,
+            message: String::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.refresh();
+        self.renaming = None;
+        self.confirm_delete = false;
+        self.message = String::new();
+    }
+
+    fn refresh(&mut self) {
+        let selected = self.instance_table.state.selected();
+        self.instance_table = StatefulTable::with_items(instance::list_instances());
+        if let Some(selected) = selected {
+            if selected < self.instance_table.items.len() {
+                self.instance_table.state.select(Some(selected));
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<Instance> {
+        self.instance_table
+            .items
+            .get(self.instance_table.state.selected().unwrap_or(0))
+            .cloned()
+    }
+
+    fn launch(&mut self, instance: Instance, mode: LaunchMode) -> Action {
+        let min_version = self
+            .all_versions
+            .iter()
+            .find(|version| version.id == instance.minecraft_version);
+
+        match (
+            min_version,
+            self.modding_handler.get_loader_by_name(&instance.loader),
+        ) {
+            (Some(min_version), Some(loader)) => Action::NextTab(Tab::Download(
+                min_version.clone(),
+                self.versions.clone(),
+                loader,
+                instance.loader_version.clone(),
+                Some(instance.profile_id),
+                mode,
+            )),
+            (None, _) => {
+                self.message = format!("Unknown Minecraft version {}", instance.minecraft_version);
+                Action::None
+            }
+            (_, None) => {
+                self.message = format!("Unknown mod loader {}", instance.loader);
+                Action::None
+            }
+        }
+    }
+}
+
+impl TabTrait for InstanceTab {
+    fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let rows: Vec<Row> = self
+            .instance_table
+            .items
+            .iter()
+            .map(|instance| {
+                Row::new(vec![
+                    Cell::from(Span::raw(instance.name.clone())),
+                    Cell::from(Span::raw(instance.minecraft_version.clone())),
+                    Cell::from(Span::raw(match &instance.loader_version {
+                        None => instance.loader.clone(),
+                        Some(loader_version) => format!("{} {}", instance.loader, loader_version),
+                    })),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(rows)
+            .block(Block::default().borders(Borders::ALL).title("Instances"))
+            .header(Row::new(vec!["Name", "Version", "Mod Loader"]))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ")
+            .widths(&[
+                Constraint::Ratio(2, 5),
+                Constraint::Ratio(1, 5),
+                Constraint::Ratio(2, 5),
+            ]);
+
+        f.render_stateful_widget(table, chunks[0], &mut self.instance_table.state);
+
+        let input = Paragraph::new(vec![Spans::from(self.renaming.clone().unwrap_or_default())])
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("New name")
+                    .border_style(Style::default().fg(match self.renaming {
+                        None => Color::White,
+                        Some(_) => Color::Yellow,
+                    })),
+            );
+        f.render_widget(input, chunks[1]);
+
+        let message = Paragraph::new(vec![Spans::from(self.message.clone())])
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(message, chunks[2]);
+    }
+
+    fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+        if let Some(mut name) = self.renaming.take() {
+            match key_code {
+                KeyCode::Enter => {
+                    if let Some(instance) = self.selected() {
+                        match instance::rename_instance(&instance, &name) {
+                            Ok(renamed) => {
+                                self.message = format!("Renamed to {}", renamed.name);
+                                self.refresh();
+                            }
+                            Err(err) => self.message = err,
+                        }
+                    }
+                }
+                KeyCode::Left => {}
+                KeyCode::Backspace => {
+                    name.pop();
+                    self.renaming = Some(name);
+                }
+                KeyCode::Char(chr) => {
+                    name.push(chr);
+                    self.renaming = Some(name);
+                }
+                _ => self.renaming = Some(name),
+            }
+            return Action::None;
+        }
+
+        let confirm_delete = self.confirm_delete;
+        self.confirm_delete = false;
+
+        match key_code {
+            KeyCode::Enter => match self.selected() {
+                None => Action::None,
+                Some(instance) => self.launch(instance, LaunchMode::Play),
+            },
+            KeyCode::Up | KeyCode::Char('u') => {
+                if !self.instance_table.items.is_empty() {
+                    self.instance_table.previous();
+                }
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('d') => {
+                if !self.instance_table.items.is_empty() {
+                    self.instance_table.next();
+                }
+                Action::None
+            }
+            KeyCode::Left | KeyCode::Char('l') => Action::NextTab(Tab::Version),
+            KeyCode::Char('e') => {
+                if let Some(instance) = self.selected() {
+                    self.renaming = Some(instance.name);
+                    self.message = String::new();
+                }
+                Action::None
+            }
+            KeyCode::Char('p') => match self.selected() {
+                None => Action::None,
+                Some(instance) => Action::NextTab(Tab::Profile(instance.profile_id)),
+            },
+            KeyCode::Char('s') => match self.selected() {
+                None => Action::None,
+                Some(instance) => self.launch(instance, LaunchMode::Export(false)),
+            },
+            KeyCode::Char('S') => match self.selected() {
+                None => Action::None,
+                Some(instance) => self.launch(instance, LaunchMode::Export(true)),
+            },
+            KeyCode::Char('x') => {
+                if let Some(instance) = self.selected() {
+                    // Deleting an instance deletes its worlds, ask twice.
+                    if confirm_delete {
+                        self.message = match instance::delete_instance(&instance) {
+                            Ok(_) => format!("Deleted {}", instance.name),
+                            Err(err) => err,
+                        };
+                        self.refresh();
+                    } else {
+                        self.confirm_delete = true;
+                        self.message =
+                            format!("Press X again to delete {} and its worlds", instance.name);
+                    }
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn get_bindings(&self) -> Vec<TabBinding> {
+        match self.renaming {
+            Some(_) => vec![
+                TabBinding::Default(String::from("ENTER"), String::from("Rename instance")),
+                TabBinding::Default(String::from("LEFT"), String::from("Cancel")),
+            ],
+            None => vec![
+                TabBinding::Default(
+                    String::from("ENTER"),
+                    String::from("Install and Launch selected instance"),
+                ),
+                TabBinding::Default(String::from("UP"), String::from("Move selector up")),
+                TabBinding::Default(String::from("DOWN"), String::from("Move selector down")),
+                TabBinding::Default(String::from("LEFT"), String::from("Back to versions")),
+                TabBinding::Default(String::from("E"), String::from("Rename instance")),
+                TabBinding::Default(String::from("P"), String::from("Edit instance profile")),
+                TabBinding::Default(
+                    String::from("S"),
+                    String::from("Install and export a launch script, SHIFT+S with the token"),
+                ),
+                TabBinding::Default(String::from("X"), String::from("Delete instance")),
+            ],
+        }
+    }
+}
@@ -1,156 +1,1147 @@
-use crate::minecraft_launcher::app::{Action, TabBinding, TabTrait};
+use crate::minecraft_launcher::app::{Action, LaunchMode, Tab, TabBinding, TabTrait};
 use crate::minecraft_launcher::arguments;
-use crate::minecraft_launcher::arguments::LaunchOptions;
+use crate::minecraft_launcher::arguments::{Account, LaunchOptions};
+use crate::minecraft_launcher::crash::{CrashKind, CrashReport};
+use crate::minecraft_launcher::game_log::{LogLevel, LogLine};
+use crate::minecraft_launcher::history::{LaunchTarget, SessionRecord};
+use crate::minecraft_launcher::known_issues::Analyzer;
 use crate::minecraft_launcher::launch;
+use crate::minecraft_launcher::launch::LaunchCommand;
+use crate::minecraft_launcher::log4shell;
+use crate::minecraft_launcher::manifest::main::{MinVersion, Version};
 use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::modding::ModLoaderHandler;
+use crate::minecraft_launcher::monitor;
 use crate::minecraft_launcher::path;
-use crate::minecraft_launcher::rendering::utils::StatefulList;
+use crate::minecraft_launcher::profiles;
+use crate::minecraft_launcher::rendering::utils::StatefulTable;
+use crate::minecraft_launcher::session::{Session, SessionState};
+use crate::minecraft_launcher::utils;
+use crate::minecraft_launcher::validation;
+use crate::minecraft_launcher::validation::MissingFile;
 use crossterm::event::KeyCode;
-use std::io::{Read, Stdout};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Stdout;
+use std::path::PathBuf;
 
-use std::process::{Child, ChildStderr, ChildStdout};
+use std::time::Duration;
 use tui::backend::CrosstermBackend;
-use tui::layout::Rect;
-use tui::text::Span;
-use tui::widgets::{Block, Borders, List, ListItem};
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{
+    Block, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Sparkline, Table, Wrap,
+};
 use tui::Frame;
 
+// Older messages are still kept, only the last ones fit on screen.
+const SHOWN_MESSAGES: usize = 5;
+const MONITOR_HEIGHT: u16 = 6;
+
+// A launch whose arguments still hold unresolved placeholders, waiting for the user.
+struct PendingLaunch {
+    name: String,
+    command: LaunchCommand,
+    access_token: String,
+    pre_launch_command: Option<String>,
+    post_exit_command: Option<String>,
+    analyzer: Analyzer,
+    record: SessionRecord,
+    profile_id: String,
+    debug_port: Option<u16>,
+    recording: Option<PathBuf>,
+    // Added to the session once started.
+    messages: Vec<String>,
+    unresolved: Vec<String>,
+}
+
+// Files missing for a launch, shown instead of starting a game that would crash.
+struct Repair {
+    missing: Vec<MissingFile>,
+    target: LaunchTarget,
+    profile_id: String,
+    mode: LaunchMode,
+}
+
 pub struct GameLogTab {
-    launch_options: Option<LaunchOptions>,
-    game_logs: StatefulList<String>,
-    child_process: Option<Child>,
-    child_stdout: Option<ChildStdout>,
-    child_stderr: Option<ChildStderr>,
+    all_versions: Vec<MinVersion>,
+    versions: Vec<Version>,
+    modding_handler: ModLoaderHandler,
+    sessions: StatefulTable<Session>,
+    log_state: ListState,
+    // Keeps the last line selected while new ones come in.
+    follow: bool,
+    level_filter: Option<LogLevel>,
+    logger_filter: Option<String>,
+    editing_logger: Option<String>,
+    crash_index: usize,
+    crash_scroll: u16,
+    show_crash: bool,
+    // Launches that failed before a session existed.
+    messages: Vec<String>,
+    repair: Option<Repair>,
+    pending: Option<PendingLaunch>,
 }
 
 impl GameLogTab {
-    pub fn new() -> GameLogTab {
+    pub fn new(all_versions: Vec<MinVersion>, versions: Vec<Version>) -> GameLogTab {
         GameLogTab {
-            launch_options: None,
-            game_logs: StatefulList::new(),
-            child_process: None,
-            child_stdout: None,
-            child_stderr: None,
+            all_versions,
+            versions,
+            modding_handler: ModLoaderHandler::new(),
+            sessions: StatefulTable::new(),
+            log_state: ListState::default(),
+            follow: true,
+            level_filter: None,
+            logger_filter: None,
+            editing_logger: None,
+            crash_index: 0,
+            crash_scroll: 0,
+            show_crash: false,
+            messages: vec![],
+            repair: None,
+            pending: None,
         }
     }
 
     pub fn init(
         &mut self,
         version: &version::Main,
-        player_name: String,
-        player_uuid: String,
-        player_token: String,
-        user_type: String,
+        profile_id: Option<String>,
+        mode: LaunchMode,
+        target: Option<LaunchTarget>,
+        account: Account,
     ) {
-        match LaunchOptions::from_version(
-            version,
-            player_name,
-            player_uuid,
-            player_token,
-            user_type,
-        ) {
-            Ok(launch_options) => {
-                self.launch_options = Some(launch_options);
+        let mut launch_options = match LaunchOptions::from_version(version, &account) {
+            Ok(launch_options) => launch_options,
+            Err(err) => {
+                self.messages.push(err.to_string());
+                return;
             }
-            Err(_err) => {}
         };
 
-        match self.launch_options.clone() {
-            None => {}
-            Some(mut launch_options) => {
-                if let Some(args) = arguments::get_args_from_manifest(version, &launch_options) {
-                    if let Ok(java_exe) = path::get_java_executable_path(version) {
-                        self.child_process = Some(launch::main(
-                            java_exe,
-                            launch_options.fill_argument_list(args),
-                        ));
-                    }
+        let (profile_id, profile) =
+            profiles::get_version_profile(profile_id.as_deref().unwrap_or(&version.id));
+        if let Err(err) = launch_options.apply_profile(&profile) {
+            self.messages.push(err);
+            return;
+        }
+
+        let settings = profile.rusty_craft.clone().unwrap_or_default();
+
+        // A config that can't be written leaves the manifest's one, better than no log at all.
+        let log4j_notice = match log4shell::get_mitigation(version) {
+            None => None,
+            Some((log4j_version, mitigation)) => Some(match mitigation.get_jvm_args() {
+                Ok(args) => {
+                    launch_options.log4j_mitigation = Some((mitigation, args));
+                    mitigation.get_notice(&log4j_version)
                 }
+                Err(err) => format!(
+                    "log4j {} is vulnerable to Log4Shell and couldn't be protected: {}",
+                    log4j_version, err
+                ),
+            }),
+        };
+
+        let args = match arguments::get_args_from_manifest(version, &launch_options) {
+            Some(args) => args,
+            None => {
+                self.messages.push(format!(
+                    "{} has no launch arguments in its manifest",
+                    version.id
+                ));
+                return;
             }
         };
+
+        let target = target.unwrap_or_else(|| LaunchTarget {
+            minecraft_version: version
+                .inherits_from
+                .clone()
+                .unwrap_or_else(|| version.id.clone()),
+            loader: String::from("Vanilla"),
+            loader_version: None,
+        });
+
+        // Downloading the version again installs the runtime it asks for.
+        let java_exe = match path::get_java_executable_path(version) {
+            Ok(java_exe) => java_exe,
+            Err(err) => {
+                let component = version
+                    .java_version
+                    .as_ref()
+                    .map_or("jre-legacy", |java_version| java_version.component.as_str());
+                self.messages.push(err.to_string());
+                self.repair = Some(Repair {
+                    missing: vec![MissingFile {
+                        description: String::from("Java runtime"),
+                        path: path::get_java_folder_path_sub(component).unwrap_or_default(),
+                    }],
+                    target,
+                    profile_id,
+                    mode,
+                });
+                return;
+            }
+        };
+
+        let game_dir = PathBuf::from(&launch_options.game_directory);
+        fs::create_dir_all(&game_dir).unwrap_or(());
+        if let Some(recordings) = launch_options
+            .flight_recording
+            .as_ref()
+            .and_then(|recording| recording.parent())
+        {
+            fs::create_dir_all(recordings).unwrap_or(());
+        }
+
+        let missing = validation::find_missing_files(version, &launch_options, &java_exe);
+        if !missing.is_empty() {
+            self.repair = Some(Repair {
+                missing,
+                target,
+                profile_id,
+                mode,
+            });
+            return;
+        }
+
+        let mut hook_env = HashMap::new();
+        hook_env.insert("RUSTY_CRAFT_PROFILE_ID".to_string(), profile_id.clone());
+        hook_env.insert("RUSTY_CRAFT_VERSION".to_string(), version.id.clone());
+        hook_env.insert(
+            "RUSTY_CRAFT_GAME_DIR".to_string(),
+            launch_options.game_directory.clone(),
+        );
+        hook_env.insert(
+            "RUSTY_CRAFT_JAVA".to_string(),
+            java_exe.display().to_string(),
+        );
+        hook_env.insert(
+            "RUSTY_CRAFT_INSTANCE".to_string(),
+            settings.instance.clone().unwrap_or_default(),
+        );
+
+        let quick_play_warning = arguments::get_quick_play_warning(&args, &launch_options);
+        let (args, unresolved) = launch_options.fill_argument_list(args);
+
+        let launch_command = LaunchCommand {
+            java_path: java_exe,
+            game_dir,
+            args,
+            wrapper: settings
+                .wrapper_command
+                .as_deref()
+                .map(arguments::split_arguments)
+                .unwrap_or_default(),
+            env: settings
+                .environment
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = value.map(|value| launch_options.fill_placeholders(&value));
+                    (name, value)
+                })
+                .collect(),
+            clear_env: settings.clear_environment.unwrap_or(false),
+            hook_env,
+        };
+
+        // Exports go through the same steps as a launch, without starting the game.
+        if let LaunchMode::Export(include_token) = mode {
+            let result =
+                launch::write_script(&launch_command, &launch_options.player_token, include_token);
+            self.push_message(match result {
+                Ok(script_path) => format!("Exported {}", script_path.display()),
+                Err(err) => err,
+            });
+            return;
+        }
+
+        let analyzer = Analyzer::load(
+            version
+                .java_version
+                .as_ref()
+                .map(|java_version| java_version.major_version),
+        );
+
+        let record = SessionRecord::new(
+            version.id.clone(),
+            target,
+            settings.instance.clone(),
+            profile_id.clone(),
+            account.name.clone(),
+        );
+
+        let mut messages = vec![];
+        if let Some(port) = launch_options.debug_port {
+            messages.push(match launch_options.debug_suspend {
+                true => format!("Waiting for a debugger on port {} before starting", port),
+                false => format!("Debugger can attach on port {}", port),
+            });
+        }
+        if let Some(notice) = log4j_notice {
+            messages.push(notice);
+        }
+        if let Some(warning) = quick_play_warning {
+            messages.push(warning);
+        }
+
+        let launch = PendingLaunch {
+            name: settings.instance.unwrap_or_else(|| version.id.clone()),
+            command: launch_command,
+            access_token: launch_options.player_token.clone(),
+            pre_launch_command: settings.pre_launch_command,
+            post_exit_command: settings.post_exit_command,
+            analyzer,
+            record,
+            profile_id,
+            debug_port: launch_options.debug_port,
+            recording: launch_options.flight_recording.clone(),
+            messages,
+            unresolved,
+        };
+
+        // The game would get the placeholders as they are, let the user decide first.
+        if launch.unresolved.is_empty() {
+            self.start(launch);
+        } else {
+            self.pending = Some(launch);
+        }
     }
-}
 
-impl TabTrait for GameLogTab {
-    fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
-        let list_items: Vec<ListItem> = self
-            .game_logs
+    fn start(&mut self, launch: PendingLaunch) {
+        match Session::start(
+            launch.name,
+            launch.command,
+            launch.access_token,
+            launch.pre_launch_command,
+            launch.post_exit_command,
+            launch.analyzer,
+            launch.record,
+        ) {
+            Ok(mut session) => {
+                session.debug_port = launch.debug_port;
+                session.recording = launch.recording;
+                session.messages.extend(launch.messages);
+                for name in launch.unresolved {
+                    session.messages.push(format!(
+                        "Unresolved placeholder ${{{}}} passed to the game",
+                        name
+                    ));
+                }
+                self.sessions.items.push(session);
+                self.select_session(self.sessions.items.len() - 1);
+                profiles::touch_profile(&launch.profile_id).unwrap_or(());
+            }
+            Err(err) => self.messages.push(err),
+        }
+    }
+
+    /// Number of games still running, asked before quitting.
+    pub fn get_running_count(&self) -> usize {
+        self.sessions
             .items
             .iter()
-            .map(|line| ListItem::new(Span::raw(line)))
-            .collect();
+            .filter(|session| session.is_running())
+            .count()
+    }
 
-        let list = List::new(list_items)
-            .block(Block::default().borders(Borders::ALL))
-            .highlight_symbol("> ");
+    pub fn get_active_count(&self) -> usize {
+        self.sessions
+            .items
+            .iter()
+            .filter(|session| session.is_active())
+            .count()
+    }
 
-        f.render_stateful_widget(list, area, &mut self.game_logs.state)
+    pub fn stop_all(&mut self) {
+        for session in self.sessions.items.iter_mut() {
+            session.stop().unwrap_or(());
+        }
     }
 
-    fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+    pub fn kill_all(&mut self) {
+        for session in self.sessions.items.iter_mut() {
+            session.kill().unwrap_or(());
+        }
+    }
+
+    /// Reads every session, the launcher keeps them going whatever tab is shown.
+    pub fn poll_sessions(&mut self) {
+        let selected = self.sessions.state.selected();
+        let level_filter = self.level_filter;
+        let logger_filter = self.logger_filter.clone();
+
+        for (i, session) in self.sessions.items.iter_mut().enumerate() {
+            let dropped = session.poll();
+
+            if Some(i) == selected && !self.follow {
+                if let Some(selected_line) = self.log_state.selected() {
+                    // Old lines were dropped, keep the selection on the same line.
+                    let dropped = dropped
+                        .iter()
+                        .filter(|line| is_visible(line, level_filter, logger_filter.as_deref()))
+                        .count();
+                    self.log_state
+                        .select(Some(selected_line.saturating_sub(dropped)));
+                }
+            }
+        }
+
+        if self.follow {
+            self.select_last();
+        }
+        self.check_new_crash();
+    }
+
+    fn session(&self) -> Option<&Session> {
+        self.sessions
+            .items
+            .get(self.sessions.state.selected().unwrap_or(0))
+    }
+
+    fn session_mut(&mut self) -> Option<&mut Session> {
+        self.sessions
+            .items
+            .get_mut(self.sessions.state.selected().unwrap_or(0))
+    }
+
+    fn select_session(&mut self, index: usize) {
+        self.sessions.state.select(Some(index));
+        self.follow = true;
+        self.show_crash = false;
+        self.select_last();
+        self.check_new_crash();
+    }
+
+    fn check_new_crash(&mut self) {
+        if let Some(session) = self.session_mut() {
+            if session.new_crash {
+                session.new_crash = false;
+                self.crash_index = 0;
+                self.crash_scroll = 0;
+                self.show_crash = true;
+            }
+        }
+    }
+
+    fn crash_reports(&self) -> &[CrashReport] {
+        match self.session() {
+            None => &[],
+            Some(session) => &session.crash_reports,
+        }
+    }
+
+    fn push_message(&mut self, message: String) {
+        match self.session_mut() {
+            None => self.messages.push(message),
+            Some(session) => session.messages.push(message),
+        }
+    }
+
+    fn export_script(&mut self, include_token: bool) {
+        let result = match self.session() {
+            None => Err(String::from(
+                "No game to export, export a version or an instance from their tab",
+            )),
+            Some(session) => {
+                launch::write_script(&session.command, &session.access_token, include_token)
+            }
+        };
+
+        self.push_message(match result {
+            Ok(script_path) => format!("Exported {}", script_path.display()),
+            Err(err) => err,
+        });
+    }
+
+    fn on_pending_key_press(&mut self, key_code: KeyCode) -> Action {
         match key_code {
-            KeyCode::Up => {
-                self.game_logs.previous();
-                Action::None
+            KeyCode::Char('l') => {
+                if let Some(launch) = self.pending.take() {
+                    self.start(launch);
+                }
             }
-            KeyCode::Down => {
-                self.game_logs.next();
+            KeyCode::Char('c') => self.pending = None,
+            _ => {}
+        }
+
+        Action::None
+    }
+
+    fn on_repair_key_press(&mut self, key_code: KeyCode) -> Action {
+        match key_code {
+            KeyCode::Char('r') => match self.repair.take() {
+                None => Action::None,
+                Some(repair) => self.reinstall(repair),
+            },
+            KeyCode::Char('c') => {
+                self.repair = None;
                 Action::None
             }
             _ => Action::None,
         }
     }
 
-    fn tick(&mut self) -> Action {
-        if self.child_process.is_some() {
-            let mut child = self.child_process.take().unwrap();
-            self.child_stdout = child.stdout.take();
-            self.child_stderr = child.stderr.take();
-            self.child_process = Some(child);
+    // Installing again downloads whatever is missing, then launches like the first time.
+    fn reinstall(&mut self, repair: Repair) -> Action {
+        let min_version = self
+            .all_versions
+            .iter()
+            .find(|version| version.id == repair.target.minecraft_version);
+
+        match (
+            min_version,
+            self.modding_handler
+                .get_loader_by_name(&repair.target.loader),
+        ) {
+            (Some(min_version), Some(loader)) => Action::NextTab(Tab::Download(
+                min_version.clone(),
+                self.versions.clone(),
+                loader,
+                repair.target.loader_version,
+                Some(repair.profile_id),
+                repair.mode,
+            )),
+            (None, _) => {
+                self.push_message(format!(
+                    "Unknown Minecraft version {}",
+                    repair.target.minecraft_version
+                ));
+                Action::None
+            }
+            (_, None) => {
+                self.push_message(format!("Unknown mod loader {}", repair.target.loader));
+                Action::None
+            }
         }
+    }
 
-        if self.child_process.is_some()
-            && self.child_stdout.is_some()
-            && self.child_stderr.is_some()
-        {
-            let mut stdout = self.child_stdout.take().unwrap();
-            let mut stderr = self.child_stderr.take().unwrap();
+    fn on_crash_key_press(&mut self, key_code: KeyCode) -> Action {
+        match key_code {
+            KeyCode::Up => self.crash_scroll = self.crash_scroll.saturating_sub(1),
+            KeyCode::Down => self.crash_scroll = self.crash_scroll.saturating_add(1),
+            KeyCode::PageUp => self.crash_scroll = self.crash_scroll.saturating_sub(20),
+            KeyCode::PageDown => self.crash_scroll = self.crash_scroll.saturating_add(20),
+            KeyCode::Char('n') => {
+                self.crash_index = (self.crash_index + 1) % self.crash_reports().len().max(1);
+                self.crash_scroll = 0;
+            }
+            KeyCode::Char('y') => {
+                if let Some(report) = self.crash_reports().get(self.crash_index) {
+                    let path = report.path.display().to_string();
+                    let message = match utils::copy_to_clipboard(&path) {
+                        // Only the terminal knows if it supports OSC 52, the path stays readable here.
+                        Ok(_) => format!("Sent {} to the terminal clipboard", path),
+                        Err(err) => err,
+                    };
+                    self.push_message(message);
+                }
+            }
+            KeyCode::Char('c') => self.show_crash = false,
+            _ => {}
+        }
+
+        Action::None
+    }
+
+    fn select_last(&mut self) {
+        match self.visible_count() {
+            0 => self.log_state.select(None),
+            count => self.log_state.select(Some(count - 1)),
+        }
+    }
+
+    fn visible_count(&self) -> usize {
+        match self.session() {
+            None => 0,
+            Some(session) => session
+                .log
+                .lines
+                .iter()
+                .filter(|line| is_visible(line, self.level_filter, self.logger_filter.as_deref()))
+                .count(),
+        }
+    }
+
+    // Changing the filters changes the indexes, the tail is the only stable place.
+    fn on_filter_change(&mut self) {
+        self.follow = true;
+        self.select_last();
+    }
+}
+
+impl TabTrait for GameLogTab {
+    fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let (diagnoses, messages) = match self.session() {
+            None => (vec![], self.messages.clone()),
+            Some(session) => (session.diagnoses.clone(), session.messages.clone()),
+        };
+        let messages: Vec<String> = messages
+            .iter()
+            .skip(messages.len().saturating_sub(SHOWN_MESSAGES))
+            .cloned()
+            .collect();
+
+        let monitored = self.session().map_or(false, |session| {
+            session.is_running() && session.monitor.latest.is_some()
+        });
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(match self.sessions.items.len() {
+                    0 => 0,
+                    len => len.min(4) as u16 + 3,
+                }),
+                Constraint::Length(match monitored {
+                    true => MONITOR_HEIGHT,
+                    false => 0,
+                }),
+                Constraint::Min(0),
+                Constraint::Length(match diagnoses.len() {
+                    0 => 0,
+                    len => len as u16 * 2 + 2,
+                }),
+                Constraint::Length(match messages.len() {
+                    0 => 0,
+                    len => len as u16 + 2,
+                }),
+            ])
+            .split(area);
 
-            let mut stdout_string = String::new();
-            stdout.read_to_string(&mut stdout_string);
+        if !self.sessions.items.is_empty() {
+            let rows: Vec<Row> = self
+                .sessions
+                .items
+                .iter()
+                .map(|session| {
+                    Row::new(vec![
+                        Cell::from(Span::raw(session.name.clone())),
+                        Cell::from(Span::styled(
+                            session.state.get_name(),
+                            match session.state {
+                                SessionState::Running => Style::default().fg(Color::Green),
+                                SessionState::PreLaunch | SessionState::Stopping => {
+                                    Style::default().fg(Color::Yellow)
+                                }
+                                SessionState::Crashed(_) | SessionState::Failed => {
+                                    Style::default().fg(Color::Red)
+                                }
+                                SessionState::Exited(_) | SessionState::Killed => Style::default(),
+                            },
+                        )),
+                        Cell::from(Span::raw(format_duration(session.get_uptime()))),
+                        Cell::from(Span::raw(
+                            session
+                                .debug_port
+                                .map(|port| port.to_string())
+                                .unwrap_or_default(),
+                        )),
+                    ])
+                })
+                .collect();
 
-            let mut stderr_string = String::new();
-            stderr.read_to_string(&mut stderr_string);
+            let table = Table::new(rows)
+                .block(Block::default().borders(Borders::ALL).title("Sessions"))
+                .header(Row::new(vec!["Name", "State", "Uptime", "Debug Port"]))
+                .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+                .highlight_symbol("> ")
+                .widths(&[
+                    Constraint::Ratio(2, 5),
+                    Constraint::Ratio(1, 5),
+                    Constraint::Ratio(1, 5),
+                    Constraint::Ratio(1, 5),
+                ]);
+            f.render_stateful_widget(table, chunks[0], &mut self.sessions.state);
+        }
+
+        if let Some(session) = self.session().filter(|_| monitored) {
+            render_monitor(f, chunks[1], session);
+        }
 
-            let mut lines: Vec<String> = Vec::new();
+        let list_items: Vec<ListItem> = match self.session() {
+            None => vec![],
+            Some(session) => session
+                .log
+                .lines
+                .iter()
+                .filter(|line| is_visible(line, self.level_filter, self.logger_filter.as_deref()))
+                .map(|line| ListItem::new(get_log_spans(line)))
+                .collect(),
+        };
 
-            lines.push("==========Stdout=========".to_string());
+        let mut title = String::from("Game log");
+        if let Some(level) = self.level_filter {
+            title.push_str(&format!(" [{}+]", level.get_name()));
+        }
+        match (&self.editing_logger, &self.logger_filter) {
+            (Some(editing), _) => title.push_str(&format!(" [logger: {}_]", editing)),
+            (None, Some(filter)) => title.push_str(&format!(" [logger: {}]", filter)),
+            (None, None) => {}
+        }
+        if self.follow {
+            title.push_str(" (following)");
+        }
 
-            let stdout_lines: Vec<&str> = stdout_string.split('\n').collect();
+        let list = List::new(list_items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_symbol("> ");
 
-            for stdout_line in stdout_lines {
-                lines.push(stdout_line.to_string());
+        match self.crash_reports().get(self.crash_index) {
+            _ if self.repair.is_some() => {
+                if let Some(repair) = &self.repair {
+                    render_repair(f, chunks[2], repair)
+                }
             }
+            _ if self.pending.is_some() => {
+                if let Some(launch) = &self.pending {
+                    render_pending(f, chunks[2], launch)
+                }
+            }
+            Some(report) if self.show_crash => {
+                render_crash_report(f, chunks[2], report, self.crash_scroll)
+            }
+            _ => f.render_stateful_widget(list, chunks[2], &mut self.log_state),
+        }
 
-            lines.push("==========Stderr=========".to_string());
+        if !diagnoses.is_empty() {
+            let mut diagnosis_lines: Vec<Spans> = vec![];
+            for diagnosis in &diagnoses {
+                diagnosis_lines.push(Spans::from(Span::styled(
+                    diagnosis.diagnosis.clone(),
+                    Style::default().fg(Color::Yellow),
+                )));
+                diagnosis_lines.push(Spans::from(format!("  {}", diagnosis.fix)));
+            }
+            let diagnoses = Paragraph::new(diagnosis_lines)
+                .block(Block::default().borders(Borders::ALL).title("Known issues"))
+                .wrap(Wrap { trim: false });
+            f.render_widget(diagnoses, chunks[3]);
+        }
 
-            let stderr_lines: Vec<&str> = stderr_string.split('\n').collect();
+        if !messages.is_empty() {
+            let messages: Vec<Spans> = messages.into_iter().map(Spans::from).collect();
+            let messages = Paragraph::new(messages)
+                .block(Block::default().borders(Borders::ALL).title("Launcher"))
+                .wrap(Wrap { trim: true });
+            f.render_widget(messages, chunks[4]);
+        }
+    }
 
-            for stderr_line in stderr_lines {
-                lines.push(stderr_line.to_string());
+    fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+        if let Some(mut filter) = self.editing_logger.take() {
+            match key_code {
+                KeyCode::Enter => {
+                    self.logger_filter = match filter.trim() {
+                        "" => None,
+                        trimmed => Some(trimmed.to_string()),
+                    };
+                    self.on_filter_change();
+                }
+                KeyCode::Left => {}
+                KeyCode::Backspace => {
+                    filter.pop();
+                    self.editing_logger = Some(filter);
+                }
+                KeyCode::Char(chr) => {
+                    filter.push(chr);
+                    self.editing_logger = Some(filter);
+                }
+                _ => self.editing_logger = Some(filter),
             }
+            return Action::None;
+        }
+
+        if self.repair.is_some() {
+            return self.on_repair_key_press(key_code);
+        }
+
+        if self.pending.is_some() {
+            return self.on_pending_key_press(key_code);
+        }
+
+        if self.show_crash {
+            return self.on_crash_key_press(key_code);
+        }
 
-            if self.game_logs.items.len() < lines.len() {
-                self.game_logs = StatefulList::with_items_inverted(lines);
+        match key_code {
+            KeyCode::Up => {
+                if let Some(selected) = self.log_state.selected() {
+                    self.follow = false;
+                    self.log_state.select(Some(selected.saturating_sub(1)));
+                }
+            }
+            KeyCode::Down => {
+                if let Some(selected) = self.log_state.selected() {
+                    let last = self.visible_count().saturating_sub(1);
+                    self.log_state.select(Some((selected + 1).min(last)));
+                    // Reaching the bottom goes back to following the tail.
+                    self.follow = selected + 1 >= last;
+                }
             }
-            self.child_stdout = Some(stdout);
-            self.child_stderr = Some(stderr);
+            KeyCode::Left | KeyCode::Right => {
+                if self.sessions.items.len() > 1 {
+                    if key_code == KeyCode::Left {
+                        self.sessions.previous();
+                    } else {
+                        self.sessions.next();
+                    }
+                    self.select_session(self.sessions.state.selected().unwrap_or(0));
+                }
+            }
+            KeyCode::End | KeyCode::Char('f') => {
+                self.follow = true;
+                self.select_last();
+            }
+            KeyCode::Char('v') => {
+                self.level_filter = match self.level_filter {
+                    None => Some(LogLevel::Debug),
+                    Some(LogLevel::Trace) | Some(LogLevel::Debug) => Some(LogLevel::Info),
+                    Some(LogLevel::Info) => Some(LogLevel::Warn),
+                    Some(LogLevel::Warn) => Some(LogLevel::Error),
+                    Some(LogLevel::Error) | Some(LogLevel::Fatal) => None,
+                };
+                self.on_filter_change();
+            }
+            KeyCode::Char('g') => {
+                self.editing_logger = Some(self.logger_filter.clone().unwrap_or_default());
+            }
+            KeyCode::Char('c') => self.show_crash = !self.crash_reports().is_empty(),
+            KeyCode::Char('s') => {
+                if let Some(Err(err)) = self.session_mut().map(|session| session.stop()) {
+                    self.push_message(err);
+                }
+            }
+            KeyCode::Char('k') => {
+                if let Some(Err(err)) = self.session_mut().map(|session| session.kill()) {
+                    self.push_message(err);
+                }
+            }
+            KeyCode::Char('x') => {
+                if let Some(selected) = self.sessions.state.selected() {
+                    if self
+                        .session()
+                        .map_or(false, |session| !session.is_running())
+                    {
+                        self.sessions.items.remove(selected);
+                        self.select_session(
+                            selected.min(self.sessions.items.len().saturating_sub(1)),
+                        );
+                    }
+                }
+            }
+            KeyCode::Char('e') => self.export_script(false),
+            KeyCode::Char('E') => self.export_script(true),
+            _ => {}
         }
 
         Action::None
     }
 
     fn get_bindings(&self) -> Vec<TabBinding> {
-        vec![]
+        if self.repair.is_some() {
+            return vec![
+                TabBinding::Default(
+                    String::from("R"),
+                    String::from("Repair the installation and launch"),
+                ),
+                TabBinding::Default(String::from("C"), String::from("Cancel the launch")),
+            ];
+        }
+
+        if self.pending.is_some() {
+            return vec![
+                TabBinding::Default(String::from("L"), String::from("Launch anyway")),
+                TabBinding::Default(String::from("C"), String::from("Cancel the launch")),
+            ];
+        }
+
+        if self.show_crash {
+            return vec![
+                TabBinding::Default(String::from("UP"), String::from("Scroll up")),
+                TabBinding::Default(String::from("DOWN"), String::from("Scroll down")),
+                TabBinding::Enablable(
+                    String::from("N"),
+                    String::from("Next crash report"),
+                    self.crash_reports().len() > 1,
+                ),
+                TabBinding::Default(String::from("Y"), String::from("Copy report path")),
+                TabBinding::Default(String::from("C"), String::from("Back to the game log")),
+            ];
+        }
+
+        if self.editing_logger.is_some() {
+            return vec![
+                TabBinding::Default(String::from("ENTER"), String::from("Apply logger filter")),
+                TabBinding::Default(String::from("LEFT"), String::from("Cancel")),
+            ];
+        }
+
+        let running = self.session().map_or(false, |session| session.is_running());
+
+        vec![
+            TabBinding::Default(String::from("UP"), String::from("Scroll up")),
+            TabBinding::Default(String::from("DOWN"), String::from("Scroll down")),
+            TabBinding::Enablable(
+                String::from("LEFT/RIGHT"),
+                String::from("Select session"),
+                self.sessions.items.len() > 1,
+            ),
+            TabBinding::Default(
+                String::from("F/END"),
+                String::from("Follow the latest lines"),
+            ),
+            TabBinding::Default(String::from("V"), String::from("Change minimum level")),
+            TabBinding::Default(String::from("G"), String::from("Filter by logger")),
+            TabBinding::Enablable(
+                String::from("C"),
+                String::from("Show crash report"),
+                !self.crash_reports().is_empty(),
+            ),
+            TabBinding::Enablable(String::from("S"), String::from("Stop the game"), running),
+            TabBinding::Enablable(String::from("K"), String::from("Kill the game"), running),
+            TabBinding::Enablable(
+                String::from("X"),
+                String::from("Remove finished session"),
+                self.session().is_some() && !running,
+            ),
+            TabBinding::Default(String::from("E"), String::from("Export launch.sh")),
+            TabBinding::Default(
+                String::from("SHIFT+E"),
+                String::from("Export launch.sh with the access token"),
+            ),
+        ]
+    }
+}
+
+fn is_visible(line: &LogLine, level_filter: Option<LogLevel>, logger_filter: Option<&str>) -> bool {
+    let level_matches = match level_filter {
+        None => true,
+        Some(level) => line.get_level() >= level,
+    };
+    let logger_matches = match logger_filter {
+        None => true,
+        Some(filter) => line.logger.as_ref().map_or(false, |logger| {
+            logger.to_lowercase().contains(&filter.to_lowercase())
+        }),
+    };
+
+    level_matches && logger_matches
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn render_monitor(f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect, session: &Session) {
+    let sample = match session.monitor.latest {
+        None => return,
+        Some(sample) => sample,
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
+        .split(area);
+
+    let stats = Paragraph::new(vec![
+        Spans::from(format!("CPU: {:.0}%", sample.cpu_percent)),
+        Spans::from(format!(
+            "Memory: {}",
+            monitor::format_bytes(sample.resident_bytes)
+        )),
+        Spans::from(format!("Threads: {}", sample.threads)),
+        Spans::from(format!("Uptime: {}", format_duration(session.get_uptime()))),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("Process"));
+    f.render_widget(stats, chunks[0]);
+
+    // Only the samples fitting in the widget are drawn, the newest ones.
+    let width = chunks[1].width.saturating_sub(2) as usize;
+    let history: Vec<u64> = session
+        .monitor
+        .memory_history
+        .iter()
+        .skip(session.monitor.memory_history.len().saturating_sub(width))
+        .copied()
+        .collect();
+    let peak = session.monitor.get_peak_memory();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Memory (peak {})", monitor::format_bytes(peak))),
+        )
+        .style(Style::default().fg(Color::Cyan))
+        .max(peak)
+        .data(&history);
+    f.render_widget(sparkline, chunks[1]);
+}
+
+fn render_repair(f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect, repair: &Repair) {
+    let items: Vec<ListItem> = repair
+        .missing
+        .iter()
+        .map(|missing| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    format!("{}: ", missing.description),
+                    Style::default().fg(Color::Red),
+                ),
+                Span::raw(missing.path.display().to_string()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!(
+        "{} missing files, the game wasn't launched",
+        repair.missing.len()
+    )));
+    f.render_widget(list, area);
+}
+
+fn render_pending(f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect, launch: &PendingLaunch) {
+    let items: Vec<ListItem> = launch
+        .unresolved
+        .iter()
+        .map(|name| {
+            ListItem::new(Spans::from(Span::styled(
+                format!("${{{}}}", name),
+                Style::default().fg(Color::Yellow),
+            )))
+        })
+        .collect();
+
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!(
+        "{} unresolved placeholders would be passed to {} as they are",
+        launch.unresolved.len(),
+        launch.name
+    )));
+    f.render_widget(list, area);
+}
+
+fn render_crash_report(
+    f: &mut Frame<CrosstermBackend<Stdout>>,
+    area: Rect,
+    report: &CrashReport,
+    scroll: u16,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(0)])
+        .split(area);
+
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let summary = vec![
+        Spans::from(vec![
+            Span::styled("Report: ", bold),
+            Span::raw(report.path.display().to_string()),
+        ]),
+        Spans::from(vec![
+            Span::styled(
+                match report.kind {
+                    CrashKind::Minecraft => "Description: ",
+                    CrashKind::Jvm => "Problematic frame: ",
+                },
+                bold,
+            ),
+            Span::raw(report.description.clone().unwrap_or_default()),
+        ]),
+        Spans::from(vec![
+            Span::styled("Exception: ", bold),
+            Span::styled(
+                report.exception.clone().unwrap_or_default(),
+                Style::default().fg(Color::Red),
+            ),
+        ]),
+        Spans::from(vec![
+            Span::styled("Suspected mods: ", bold),
+            Span::styled(
+                match report.suspected_mods.len() {
+                    0 => String::from("none found"),
+                    _ => report.suspected_mods.join(", "),
+                },
+                Style::default().fg(Color::Yellow),
+            ),
+        ]),
+    ];
+
+    let summary = Paragraph::new(summary)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(match report.kind {
+                    CrashKind::Minecraft => "Minecraft crashed",
+                    CrashKind::Jvm => "The JVM crashed",
+                }),
+        )
+        .wrap(Wrap { trim: true });
+    f.render_widget(summary, chunks[0]);
+
+    let content = Paragraph::new(report.content.as_str())
+        .block(Block::default().borders(Borders::ALL).title("Full report"))
+        .scroll((scroll, 0));
+    f.render_widget(content, chunks[1]);
+}
+
+fn get_level_style(level: LogLevel) -> Style {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => Style::default().fg(Color::DarkGray),
+        LogLevel::Info => Style::default(),
+        LogLevel::Warn => Style::default().fg(Color::Yellow),
+        LogLevel::Error => Style::default().fg(Color::Red),
+        LogLevel::Fatal => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+fn get_log_spans(line: &LogLine) -> Vec<Spans<'static>> {
+    let level_style = get_level_style(line.get_level());
+    let mut spans = vec![Span::styled(
+        line.time.format("[%H:%M:%S] ").to_string(),
+        Style::default().fg(Color::DarkGray),
+    )];
+
+    // Plain lines already carry their own header.
+    match &line.logger {
+        None => spans.push(Span::styled(line.text.clone(), level_style)),
+        Some(logger) => {
+            spans.push(Span::styled(
+                format!(
+                    "[{}/{}] ",
+                    line.thread.clone().unwrap_or_default(),
+                    line.get_level().get_name()
+                ),
+                level_style,
+            ));
+            spans.push(Span::styled(
+                format!("{}: ", logger.rsplit('.').next().unwrap_or(logger)),
+                Style::default().fg(Color::DarkGray),
+            ));
+            spans.push(Span::styled(line.text.clone(), level_style));
+        }
     }
+
+    let mut lines = vec![Spans::from(spans)];
+    if let Some(throwable) = &line.throwable {
+        for throwable_line in throwable.lines() {
+            lines.push(Spans::from(Span::styled(
+                throwable_line.to_string(),
+                Style::default().fg(Color::Red),
+            )));
+        }
+    }
+
+    lines
 }
@@ -1,7 +1,9 @@
 use crate::minecraft_launcher::app::{Action, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::arguments::Account;
 use crossterm::event::KeyCode;
 use sage_auth::auth::AuthenticateBuilder;
 
+use std::collections::BTreeMap;
 use std::io::Stdout;
 
 use tui::backend::CrosstermBackend;
@@ -19,6 +21,11 @@ pub struct LoginTab {
     pub token: String,
     pub name: String,
     pub user_type: String,
+    pub demo: bool,
+    client_id: String,
+    // Only Xbox Live logins have one, Mojang accounts leave it empty.
+    xuid: String,
+    user_properties: String,
     selected: InputBox,
     error: String,
 }
@@ -32,11 +39,28 @@ impl LoginTab {
             token: "".to_string(),
             name: "".to_string(),
             user_type: "".to_string(),
+            demo: false,
+            client_id: "".to_string(),
+            xuid: "".to_string(),
+            user_properties: "{}".to_string(),
             selected: InputBox::Account,
             error: "".to_string(),
         }
     }
 
+    pub fn get_account(&self) -> Account {
+        Account {
+            name: self.name.clone(),
+            uuid: self.uuid.to_string(),
+            access_token: self.token.clone(),
+            user_type: self.user_type.clone(),
+            client_id: self.client_id.clone(),
+            xuid: self.xuid.clone(),
+            user_properties: self.user_properties.clone(),
+            demo: self.demo,
+        }
+    }
+
     async fn login(&mut self) {
         let request = AuthenticateBuilder::default()
             .username(self.account.clone().as_str())
@@ -49,6 +73,9 @@ impl LoginTab {
             Ok(response) => {
                 self.token = response.access_token.clone();
 
+                // Accounts without a profile don't own the game.
+                self.demo = response.selected_profile.is_none();
+
                 match response.selected_profile {
                     None => {}
                     Some(profile) => {
@@ -57,6 +84,20 @@ impl LoginTab {
                     }
                 }
 
+                self.client_id = response.client_token.clone();
+                // The game expects each property name with the list of its values.
+                let mut properties: BTreeMap<String, Vec<String>> = BTreeMap::new();
+                if let Some(user) = &response.user {
+                    for property in &user.properties {
+                        properties
+                            .entry(property.name.clone())
+                            .or_default()
+                            .push(property.value.clone());
+                    }
+                }
+                self.user_properties =
+                    serde_json::to_string(&properties).unwrap_or_else(|_| "{}".to_string());
+
                 self.user_type = "mojang".to_string();
                 self.error = "".to_string();
             }
@@ -0,0 +1,443 @@
+use crate::minecraft_launcher::app::{Action, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::arguments;
+use crate::minecraft_launcher::manifest::launcher_profiles::{
+    Profile, ProfileSettings, Resolution,
+};
+use crate::minecraft_launcher::profiles;
+use crate::minecraft_launcher::rendering::utils::StatefulTable;
+use crossterm::event::KeyCode;
+
+use std::collections::HashMap;
+use std::io::Stdout;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap};
+use tui::Frame;
+
+pub struct ProfileTab {
+    profile_id: String,
+    profile: Profile,
+    field_table: StatefulTable<ProfileField>,
+    editing: Option<String>,
+    message: String,
+}
+
+impl ProfileTab {
+    pub fn new() -> ProfileTab {
+        ProfileTab {
+            profile_id: String::new(),
+            profile: Profile::default(),
+            field_table: StatefulTable::with_items(ProfileField::all()),
+            editing: None,
+            message: String::new(),
+        }
+    }
+
+    pub fn init(&mut self, version_id: &str) {
+        let (profile_id, profile) = profiles::get_version_profile(version_id);
+        self.profile_id = profile_id;
+        self.profile = profile;
+        self.editing = None;
+        self.message = String::new();
+    }
+
+    fn selected(&self) -> ProfileField {
+        self.field_table.items[self.field_table.state.selected().unwrap_or(0)]
+    }
+
+    fn save(&mut self, value: String) {
+        let mut profile = self.profile.clone();
+
+        match self.selected().set(&mut profile, value) {
+            Err(err) => self.message = err,
+            Ok(_) => match profiles::save_profile(&self.profile_id, profile.clone()) {
+                Ok(_) => {
+                    self.profile = profile;
+                    self.message = String::from("Saved");
+                }
+                Err(err) => self.message = err,
+            },
+        }
+    }
+}
+
+impl TabTrait for ProfileTab {
+    fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ])
+            .split(area);
+
+        let rows: Vec<Row> = self
+            .field_table
+            .items
+            .iter()
+            .map(|field| {
+                Row::new(vec![
+                    Cell::from(Span::raw(field.get_name())),
+                    Cell::from(Span::raw(field.get(&self.profile))),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(rows)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Profile {}", self.profile_id)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ")
+            .widths(&[Constraint::Ratio(1, 4), Constraint::Ratio(3, 4)]);
+
+        f.render_stateful_widget(table, chunks[0], &mut self.field_table.state);
+
+        let input = Paragraph::new(vec![Spans::from(self.editing.clone().unwrap_or_default())])
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.selected().get_name())
+                    .border_style(Style::default().fg(match self.editing {
+                        None => Color::White,
+                        Some(_) => Color::Yellow,
+                    })),
+            );
+        f.render_widget(input, chunks[1]);
+
+        let message = Paragraph::new(vec![Spans::from(self.message.clone())])
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(message, chunks[2]);
+    }
+
+    fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+        match self.editing.take() {
+            Some(mut value) => {
+                match key_code {
+                    KeyCode::Enter => self.save(value),
+                    KeyCode::Left => {}
+                    KeyCode::Backspace => {
+                        value.pop();
+                        self.editing = Some(value);
+                    }
+                    KeyCode::Char(chr) => {
+                        value.push(chr);
+                        self.editing = Some(value);
+                    }
+                    _ => self.editing = Some(value),
+                }
+                Action::None
+            }
+            None => match key_code {
+                KeyCode::Enter => {
+                    let field = self.selected();
+                    if field.is_toggle() {
+                        let toggled = field.get(&self.profile) != "true";
+                        self.save(toggled.to_string());
+                    } else {
+                        self.editing = Some(field.get(&self.profile));
+                        self.message = String::new();
+                    }
+                    Action::None
+                }
+                KeyCode::Up | KeyCode::Char('u') => {
+                    self.field_table.previous();
+                    Action::None
+                }
+                KeyCode::Down | KeyCode::Char('d') => {
+                    self.field_table.next();
+                    Action::None
+                }
+                KeyCode::Left | KeyCode::Char('l') => Action::NextTab(Tab::Version),
+                _ => Action::None,
+            },
+        }
+    }
+
+    fn get_bindings(&self) -> Vec<TabBinding> {
+        match self.editing {
+            Some(_) => vec![
+                TabBinding::Default(String::from("ENTER"), String::from("Save the value")),
+                TabBinding::Default(String::from("LEFT"), String::from("Cancel")),
+                TabBinding::Default(
+                    String::from("BACKSPACE/DELETE"),
+                    String::from("Remove the last character"),
+                ),
+            ],
+            None => vec![
+                TabBinding::Default(String::from("ENTER"), String::from("Edit selected setting")),
+                TabBinding::Default(String::from("UP"), String::from("Move selector up")),
+                TabBinding::Default(String::from("DOWN"), String::from("Move selector down")),
+                TabBinding::Default(String::from("LEFT"), String::from("Back to versions")),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ProfileField {
+    MinMemory,
+    MaxMemory,
+    JvmArgs,
+    GameArgs,
+    Resolution,
+    Fullscreen,
+    World,
+    Server,
+    Realm,
+    Wrapper,
+    PreLaunch,
+    PostExit,
+    DebugPort,
+    DebugSuspend,
+    FlightRecorder,
+    JavaAgents,
+    Environment,
+    ClearEnvironment,
+}
+
+impl ProfileField {
+    fn all() -> Vec<ProfileField> {
+        vec![
+            ProfileField::MinMemory,
+            ProfileField::MaxMemory,
+            ProfileField::JvmArgs,
+            ProfileField::GameArgs,
+            ProfileField::Resolution,
+            ProfileField::Fullscreen,
+            ProfileField::World,
+            ProfileField::Server,
+            ProfileField::Realm,
+            ProfileField::Wrapper,
+            ProfileField::PreLaunch,
+            ProfileField::PostExit,
+            ProfileField::DebugPort,
+            ProfileField::DebugSuspend,
+            ProfileField::FlightRecorder,
+            ProfileField::JavaAgents,
+            ProfileField::Environment,
+            ProfileField::ClearEnvironment,
+        ]
+    }
+
+    fn get_name(&self) -> String {
+        match self {
+            ProfileField::MinMemory => "Min memory",
+            ProfileField::MaxMemory => "Max memory",
+            ProfileField::JvmArgs => "JVM arguments",
+            ProfileField::GameArgs => "Game arguments",
+            ProfileField::Resolution => "Resolution (WIDTHxHEIGHT)",
+            ProfileField::Fullscreen => "Fullscreen",
+            ProfileField::World => "Open world on launch",
+            ProfileField::Server => "Join server on launch (HOST:PORT)",
+            ProfileField::Realm => "Join realm on launch",
+            ProfileField::Wrapper => "Wrapper command",
+            ProfileField::PreLaunch => "Pre-launch command",
+            ProfileField::PostExit => "Post-exit command",
+            ProfileField::DebugPort => "Debug port (JDWP)",
+            ProfileField::DebugSuspend => "Wait for the debugger",
+            ProfileField::FlightRecorder => "Record with Flight Recorder",
+            ProfileField::JavaAgents => "Java agents",
+            ProfileField::Environment => "Environment (NAME=value, -NAME removes)",
+            ProfileField::ClearEnvironment => "Clear inherited environment",
+        }
+        .to_string()
+    }
+
+    // Toggles are switched with ENTER instead of being typed.
+    fn is_toggle(&self) -> bool {
+        matches!(
+            self,
+            ProfileField::Fullscreen
+                | ProfileField::DebugSuspend
+                | ProfileField::FlightRecorder
+                | ProfileField::ClearEnvironment
+        )
+    }
+
+    fn get(&self, profile: &Profile) -> String {
+        let settings = profile.rusty_craft.clone().unwrap_or_default();
+
+        match self {
+            ProfileField::MinMemory => settings.min_memory,
+            ProfileField::MaxMemory => settings.max_memory,
+            ProfileField::JvmArgs => profile.java_args.clone(),
+            ProfileField::GameArgs => settings.game_args,
+            ProfileField::Resolution => profile
+                .resolution
+                .as_ref()
+                .map(|resolution| format!("{}x{}", resolution.width, resolution.height)),
+            ProfileField::Fullscreen => Some(settings.fullscreen.unwrap_or(false).to_string()),
+            ProfileField::World => settings.world,
+            ProfileField::Server => settings.server,
+            ProfileField::Realm => settings.realm,
+            ProfileField::Wrapper => settings.wrapper_command,
+            ProfileField::PreLaunch => settings.pre_launch_command,
+            ProfileField::PostExit => settings.post_exit_command,
+            ProfileField::DebugPort => settings.debug_port.map(|port| port.to_string()),
+            ProfileField::DebugSuspend => Some(settings.debug_suspend.unwrap_or(false).to_string()),
+            ProfileField::FlightRecorder => {
+                Some(settings.flight_recorder.unwrap_or(false).to_string())
+            }
+            ProfileField::JavaAgents => settings.java_agents,
+            ProfileField::Environment => settings.environment.as_ref().map(format_environment),
+            ProfileField::ClearEnvironment => {
+                Some(settings.clear_environment.unwrap_or(false).to_string())
+            }
+        }
+        .unwrap_or_default()
+    }
+
+    fn set(&self, profile: &mut Profile, value: String) -> Result<(), String> {
+        let value = match value.trim() {
+            "" => None,
+            trimmed => Some(trimmed.to_string()),
+        };
+
+        // Both memory fields are checked together, a new minimum can't go over the maximum.
+        let settings = profile.rusty_craft.clone().unwrap_or_default();
+        match self {
+            ProfileField::MinMemory => {
+                arguments::check_memory(value.as_deref(), settings.max_memory.as_deref())?
+            }
+            ProfileField::MaxMemory => {
+                arguments::check_memory(settings.min_memory.as_deref(), value.as_deref())?
+            }
+            _ => {}
+        }
+
+        let debug_port = match (self, &value) {
+            (ProfileField::DebugPort, Some(port)) => match port.parse::<u16>() {
+                Ok(port) if port > 0 => Some(port),
+                _ => return Err(format!("Invalid port '{}', use a number like 5005", port)),
+            },
+            _ => None,
+        };
+
+        let environment = match (self, &value) {
+            (ProfileField::Environment, Some(environment)) => Some(parse_environment(environment)?),
+            _ => None,
+        };
+
+        let settings = profile
+            .rusty_craft
+            .get_or_insert_with(ProfileSettings::default);
+
+        match self {
+            ProfileField::MinMemory => settings.min_memory = value,
+            ProfileField::MaxMemory => settings.max_memory = value,
+            ProfileField::JvmArgs => profile.java_args = value,
+            ProfileField::GameArgs => settings.game_args = value,
+            ProfileField::Wrapper => settings.wrapper_command = value,
+            ProfileField::PreLaunch => settings.pre_launch_command = value,
+            ProfileField::PostExit => settings.post_exit_command = value,
+            ProfileField::DebugPort => settings.debug_port = debug_port,
+            ProfileField::JavaAgents => settings.java_agents = value,
+            ProfileField::Environment => settings.environment = environment,
+            ProfileField::Resolution => {
+                profile.resolution = match value {
+                    None => None,
+                    Some(value) => Some(parse_resolution(&value)?),
+                }
+            }
+            ProfileField::Fullscreen
+            | ProfileField::DebugSuspend
+            | ProfileField::FlightRecorder
+            | ProfileField::ClearEnvironment => {
+                let enabled = match value.as_deref() {
+                    Some("true") => Some(true),
+                    _ => None,
+                };
+                match self {
+                    ProfileField::Fullscreen => settings.fullscreen = enabled,
+                    ProfileField::DebugSuspend => settings.debug_suspend = enabled,
+                    ProfileField::FlightRecorder => settings.flight_recorder = enabled,
+                    _ => settings.clear_environment = enabled,
+                }
+            }
+            // The game can only start in one place.
+            ProfileField::World | ProfileField::Server | ProfileField::Realm => {
+                if value.is_some() {
+                    settings.world = None;
+                    settings.server = None;
+                    settings.realm = None;
+                }
+                match self {
+                    ProfileField::World => settings.world = value,
+                    ProfileField::Server => settings.server = value,
+                    _ => settings.realm = value,
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// "NAME=value" sets a variable and "-NAME" removes it, quotes keep spaces in values.
+fn parse_environment(value: &str) -> Result<HashMap<String, Option<String>>, String> {
+    let mut environment = HashMap::new();
+
+    for entry in arguments::split_arguments(value) {
+        let (name, value) = match entry.strip_prefix('-') {
+            Some(name) => (name.to_string(), None),
+            None => match entry.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (entry.clone(), None),
+            },
+        };
+
+        if name.is_empty() || name.contains('=') || (value.is_none() && !entry.starts_with('-')) {
+            return Err(format!(
+                "Invalid variable '{}', use NAME=value or -NAME",
+                entry
+            ));
+        }
+        environment.insert(name, value);
+    }
+
+    Ok(environment)
+}
+
+fn format_environment(environment: &HashMap<String, Option<String>>) -> String {
+    let mut entries: Vec<String> = environment
+        .iter()
+        .map(|(name, value)| match value {
+            None => format!("-{}", name),
+            Some(value) if value.contains(char::is_whitespace) => {
+                format!("{}=\"{}\"", name, value)
+            }
+            Some(value) => format!("{}={}", name, value),
+        })
+        .collect();
+    entries.sort();
+
+    entries.join(" ")
+}
+
+fn parse_resolution(value: &str) -> Result<Resolution, String> {
+    let parts: Vec<&str> = value.split('x').collect();
+
+    match (
+        parts
+            .get(0)
+            .and_then(|width| width.trim().parse::<u32>().ok()),
+        parts
+            .get(1)
+            .and_then(|height| height.trim().parse::<u32>().ok()),
+    ) {
+        (Some(width), Some(height)) if parts.len() == 2 && width > 0 && height > 0 => {
+            Ok(Resolution { width, height })
+        }
+        _ => Err(format!(
+            "Invalid resolution '{}', use something like 1920x1080",
+            value
+        )),
+    }
+}
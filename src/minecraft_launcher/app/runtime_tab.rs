@@ -0,0 +1,355 @@
+use crate::minecraft_launcher::app::download_tab::Message;
+use crate::minecraft_launcher::app::{Action, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::rendering::utils::StatefulTable;
+use crate::minecraft_launcher::runtime::InstalledRuntime;
+use crate::minecraft_launcher::{path, runtime};
+use crossterm::event::KeyCode;
+
+use std::io::Stdout;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap};
+use tui::Frame;
+
+pub struct RuntimeTab {
+    installed: Vec<version::Main>,
+    runtime_table: StatefulTable<InstalledRuntime>,
+    status: Vec<String>,
+    reinstall_rx: Option<Receiver<Message>>,
+    verify_rx: Option<Receiver<Result<Vec<String>, String>>>,
+    remove_prompt: Option<InstalledRuntime>,
+}
+
+impl RuntimeTab {
+    pub fn new() -> RuntimeTab {
+        RuntimeTab {
+            installed: Vec::new(),
+            runtime_table: StatefulTable::new(),
+            status: Vec::new(),
+            reinstall_rx: None,
+            verify_rx: None,
+            remove_prompt: None,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.installed = match path::get_minecraft_sub_folder("versions") {
+            None => vec![],
+            Some(version_folder) => crate::minecraft_launcher::get_local_versions(&version_folder),
+        };
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        let selected = self.runtime_table.state.selected();
+        self.runtime_table =
+            StatefulTable::with_items(runtime::list_installed_runtimes(&self.installed));
+        if let Some(selected) = selected {
+            if selected < self.runtime_table.items.len() {
+                self.runtime_table.state.select(Some(selected));
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<InstalledRuntime> {
+        self.runtime_table
+            .items
+            .get(self.runtime_table.state.selected().unwrap_or(0))
+            .cloned()
+    }
+
+    fn is_busy(&self) -> bool {
+        self.reinstall_rx.is_some() || self.verify_rx.is_some()
+    }
+
+    fn verify(&mut self, runtime: InstalledRuntime) {
+        let (tx, rx) = mpsc::channel();
+        self.status = vec![format!(
+            "Verifying {} ({})...",
+            runtime.component, runtime.os
+        )];
+
+        thread::spawn(move || {
+            tx.send(runtime::verify_runtime(&runtime)).unwrap_or(());
+        });
+
+        self.verify_rx = Some(rx);
+    }
+
+    fn reinstall(&mut self, runtime: InstalledRuntime) {
+        let (tx, rx) = mpsc::channel();
+        self.status = vec![format!(
+            "Reinstalling {} ({})...",
+            runtime.component, runtime.os
+        )];
+
+        thread::spawn(move || {
+            runtime::reinstall_runtime(&runtime, tx);
+        });
+
+        self.reinstall_rx = Some(rx);
+    }
+
+    fn remove(&mut self, runtime: InstalledRuntime) {
+        self.status = vec![match runtime::remove_runtime(&runtime) {
+            Ok(_) => format!("Removed {} ({})", runtime.component, runtime.os),
+            Err(err) => err,
+        }];
+        self.refresh();
+    }
+
+    fn render_remove_prompt(
+        &self,
+        f: &mut Frame<CrosstermBackend<Stdout>>,
+        area: Rect,
+        runtime: &InstalledRuntime,
+    ) {
+        let prompt_area = Rect::new(
+            area.x + area.width.saturating_sub(50) / 2,
+            area.y + area.height.saturating_sub(7) / 2,
+            area.width.min(50),
+            area.height.min(7),
+        );
+
+        let lines = vec![
+            Spans::from(format!("Remove {} ({})?", runtime.component, runtime.os)),
+            Spans::from(""),
+            Spans::from(""),
+            Spans::from("Y: Remove    N: Cancel"),
+        ];
+
+        let prompt = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Remove runtime")
+                    .border_style(Style::default().fg(Color::Yellow)),
+            )
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(Clear, prompt_area);
+        f.render_widget(prompt, prompt_area);
+    }
+
+    fn render_runtime_table(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let runtime_rows: Vec<Row> = self
+            .runtime_table
+            .items
+            .iter()
+            .map(|r| {
+                let cells = vec![
+                    Cell::from(Span::raw(r.component.clone())),
+                    Cell::from(Span::raw(r.os.clone())),
+                    Cell::from(Span::raw(r.version.clone().unwrap_or_default())),
+                    Cell::from(Span::raw(runtime::format_size(r.size))),
+                    Cell::from(Span::raw(if r.is_used() {
+                        r.used_by.join(", ")
+                    } else {
+                        String::from("Unused")
+                    })),
+                ];
+                Row::new(cells)
+            })
+            .collect();
+
+        let table = Table::new(runtime_rows)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Java Runtimes"),
+            )
+            .header(Row::new(vec![
+                "Component",
+                "OS",
+                "Version",
+                "Size",
+                "Used by",
+            ]))
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ")
+            .widths(&[
+                Constraint::Ratio(3, 16),
+                Constraint::Ratio(2, 16),
+                Constraint::Ratio(3, 16),
+                Constraint::Ratio(2, 16),
+                Constraint::Ratio(6, 16),
+            ]);
+
+        f.render_stateful_widget(table, area, &mut self.runtime_table.state);
+    }
+}
+
+impl TabTrait for RuntimeTab {
+    fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Ratio(2, 3), Constraint::Ratio(1, 3)])
+            .split(area);
+
+        self.render_runtime_table(f, chunks[0]);
+
+        let status: Vec<Spans> = self
+            .status
+            .iter()
+            .map(|line| Spans::from(line.clone()))
+            .collect();
+
+        let paragraph = Paragraph::new(status)
+            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(paragraph, chunks[1]);
+
+        if let Some(runtime) = &self.remove_prompt {
+            self.render_remove_prompt(f, area, runtime);
+        }
+    }
+
+    fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+        if let Some(runtime) = self.remove_prompt.take() {
+            match key_code {
+                KeyCode::Char('y') => self.remove(runtime),
+                KeyCode::Char('n') => {}
+                _ => self.remove_prompt = Some(runtime),
+            }
+            return Action::None;
+        }
+
+        match key_code {
+            KeyCode::Up | KeyCode::Char('u') => {
+                if !self.runtime_table.items.is_empty() {
+                    self.runtime_table.previous();
+                }
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('d') => {
+                if !self.runtime_table.items.is_empty() {
+                    self.runtime_table.next();
+                }
+                Action::None
+            }
+            KeyCode::Left | KeyCode::Char('l') => {
+                if self.is_busy() {
+                    Action::None
+                } else {
+                    Action::NextTab(Tab::Version)
+                }
+            }
+            KeyCode::Char('v') => {
+                if let (false, Some(runtime)) = (self.is_busy(), self.selected()) {
+                    self.verify(runtime);
+                }
+                Action::None
+            }
+            KeyCode::Char('r') => {
+                if let (false, Some(runtime)) = (self.is_busy(), self.selected()) {
+                    self.reinstall(runtime);
+                }
+                Action::None
+            }
+            KeyCode::Char('x') => {
+                if let (false, Some(runtime)) = (self.is_busy(), self.selected()) {
+                    if runtime.is_used() {
+                        self.status = vec![format!(
+                            "{} is still used by {}",
+                            runtime.component,
+                            runtime.used_by.join(", ")
+                        )];
+                    } else {
+                        self.remove_prompt = Some(runtime);
+                    }
+                }
+                Action::None
+            }
+            KeyCode::Char('p') => {
+                if !self.is_busy() {
+                    self.status = runtime::remove_unused_runtimes(&self.installed)
+                        .into_iter()
+                        .map(|result| match result {
+                            Ok(removed) => format!("Removed {}", removed),
+                            Err(err) => err,
+                        })
+                        .collect();
+                    if self.status.is_empty() {
+                        self.status.push(String::from("No unused runtime"));
+                    }
+                    self.refresh();
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn tick(&mut self) -> Action {
+        if let Some(rx) = &self.verify_rx {
+            if let Ok(result) = rx.try_recv() {
+                match result {
+                    Ok(problems) => {
+                        if problems.is_empty() {
+                            self.status.push(String::from("No problem found"));
+                        } else {
+                            self.status.push(format!(
+                                "{} problem(s) found, press R to reinstall",
+                                problems.len()
+                            ));
+                            self.status.extend(problems);
+                        }
+                    }
+                    Err(err) => self.status.push(err),
+                }
+                self.verify_rx = None;
+            }
+        }
+
+        if let Some(rx) = &self.reinstall_rx {
+            let mut done = false;
+
+            loop {
+                match rx.try_recv() {
+                    Ok(Message::NewSubStep(name, _, _)) => self.status.push(name),
+                    Ok(Message::Error(err)) => self.status.push(err),
+                    Ok(_) => {}
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        done = true;
+                        break;
+                    }
+                }
+            }
+
+            if done {
+                self.reinstall_rx = None;
+                self.refresh();
+            }
+        }
+
+        Action::None
+    }
+
+    fn get_bindings(&self) -> Vec<TabBinding> {
+        if self.remove_prompt.is_some() {
+            return vec![
+                TabBinding::Default(String::from("Y"), String::from("Remove the runtime")),
+                TabBinding::Default(String::from("N"), String::from("Cancel")),
+            ];
+        }
+
+        vec![
+            TabBinding::Default(String::from("UP"), String::from("Move selector up")),
+            TabBinding::Default(String::from("DOWN"), String::from("Move selector down")),
+            TabBinding::Default(String::from("LEFT"), String::from("Back to versions")),
+            TabBinding::Default(String::from("V"), String::from("Verify runtime files")),
+            TabBinding::Default(String::from("R"), String::from("Reinstall runtime")),
+            TabBinding::Default(String::from("X"), String::from("Remove runtime")),
+            TabBinding::Default(String::from("P"), String::from("Remove unused runtimes")),
+        ]
+    }
+}
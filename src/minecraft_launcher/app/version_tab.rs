@@ -1,15 +1,18 @@
-use crate::minecraft_launcher::app::{Action, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::app::{Action, LaunchMode, Tab, TabBinding, TabTrait};
+use crate::minecraft_launcher::history::{Playtime, SessionRecord};
 use crate::minecraft_launcher::manifest::main::{MinVersion, Version};
-use crate::minecraft_launcher::modding;
+use crate::minecraft_launcher::modding::ModLoaderInstaller;
+use crate::minecraft_launcher::{history, instance, modding};
 use crate::minecraft_launcher::rendering::utils::{StatefulList, StatefulTable};
 use crossterm::event::KeyCode;
 
+use std::collections::HashMap;
 use std::io::Stdout;
 use tui::backend::CrosstermBackend;
-use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Modifier, Style};
-use tui::text::Span;
-use tui::widgets::{Block, Borders, Cell, List, ListItem, Row, Table};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, Wrap};
 use tui::Frame;
 
 pub struct VersionTab {
@@ -24,8 +27,15 @@ pub struct VersionTab {
     pub loader_version_list: StatefulList<String>,
     pub versions: Vec<Version>,
     pub modding_handler: modding::ModLoaderHandler,
+    pub playtimes: HashMap<String, Playtime>,
+    pub recent: Vec<SessionRecord>,
+    // Error of the last instance creation.
+    pub message: String,
 }
 
+// One key per entry, from 1 to 5.
+const RECENT_COUNT: usize = 5;
+
 impl VersionTab {
     pub fn build_table_state(&mut self) {
         let mut items: Vec<MinVersion> = Vec::new();
@@ -40,6 +50,40 @@ impl VersionTab {
         }
 
         self.mc_version_table = StatefulTable::with_items(items);
+        self.refresh_history();
+    }
+
+    /// Reloads playtimes and recent launches, a game may have been played since.
+    pub fn refresh_history(&mut self) {
+        let sessions = history::read_history().unwrap_or_default();
+        self.playtimes = history::get_playtimes(&sessions);
+        self.recent = history::get_recent(&sessions, RECENT_COUNT);
+    }
+
+    fn relaunch(&self, index: usize) -> Action {
+        let record = match self.recent.get(index) {
+            None => return Action::None,
+            Some(record) => record,
+        };
+        let min_version = self
+            .all_versions
+            .iter()
+            .find(|version| version.id == record.minecraft_version);
+
+        match (
+            min_version,
+            self.modding_handler.get_loader_by_name(&record.loader),
+        ) {
+            (Some(min_version), Some(loader)) => Action::NextTab(Tab::Download(
+                min_version.clone(),
+                self.versions.clone(),
+                loader,
+                record.loader_version.clone(),
+                Some(record.profile_id.clone()),
+                LaunchMode::Play,
+            )),
+            _ => Action::None,
+        }
     }
 
     pub fn build_mod_loader_list(&mut self) {
@@ -85,6 +129,7 @@ impl VersionTab {
             .items
             .iter()
             .map(|v| {
+                let playtime = self.playtimes.get(&v.id);
                 let cells = vec![
                     Cell::from(Span::raw(v.id.to_string())),
                     Cell::from(Span::raw(v._type.to_string())),
@@ -96,6 +141,14 @@ impl VersionTab {
                         .to_string(),
                     )),
                     Cell::from(Span::raw(format!("{:?}", v.release_time))),
+                    Cell::from(Span::raw(match playtime {
+                        None => String::new(),
+                        Some(playtime) => history::format_playtime(playtime.total),
+                    })),
+                    Cell::from(Span::raw(match playtime {
+                        None => String::new(),
+                        Some(playtime) => playtime.last_played.format("%Y-%m-%d %H:%M").to_string(),
+                    })),
                 ];
                 Row::new(cells)
             })
@@ -103,19 +156,59 @@ impl VersionTab {
 
         let table = Table::new(version_list)
             .block(Block::default().borders(Borders::ALL).title("Version List"))
-            .header(Row::new(vec!["Name", "Type", "Installed", "Release Date"]))
+            .header(Row::new(vec![
+                "Name",
+                "Type",
+                "Installed",
+                "Release Date",
+                "Played",
+                "Last Played",
+            ]))
             .highlight_style(Style::default().add_modifier(Modifier::BOLD))
             .highlight_symbol("> ")
             .widths(&[
-                Constraint::Ratio(5, 12),
-                Constraint::Ratio(3, 24),
-                Constraint::Ratio(5, 36),
-                Constraint::Ratio(4, 12),
+                Constraint::Ratio(2, 9),
+                Constraint::Ratio(1, 9),
+                Constraint::Ratio(1, 9),
+                Constraint::Ratio(2, 9),
+                Constraint::Ratio(1, 9),
+                Constraint::Ratio(2, 9),
             ]);
 
         f.render_stateful_widget(table, area, &mut self.mc_version_table.state);
     }
 
+    fn render_recent_list(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let recent_list: Vec<ListItem> = self
+            .recent
+            .iter()
+            .enumerate()
+            .map(|(i, record)| {
+                let name = match (&record.instance, &record.loader_version) {
+                    (Some(instance), _) => instance.clone(),
+                    (None, Some(loader_version)) => format!(
+                        "{} {} {}",
+                        record.minecraft_version, record.loader, loader_version
+                    ),
+                    (None, None) => format!("{} {}", record.minecraft_version, record.loader),
+                };
+                let started = match record.get_started() {
+                    None => String::new(),
+                    Some(started) => started.format("%Y-%m-%d %H:%M").to_string(),
+                };
+                ListItem::new(Span::raw(format!("{}. {} - {}", i + 1, name, started)))
+            })
+            .collect();
+
+        let list = List::new(recent_list).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Recently Played"),
+        );
+
+        f.render_widget(list, area)
+    }
+
     fn render_loader_list(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
         let loader_list: Vec<ListItem> = self
             .loader_list
@@ -156,6 +249,73 @@ impl VersionTab {
         f.render_stateful_widget(list, area, &mut self.loader_version_list.state)
     }
 
+    // The highlighted entry of the current list, like ENTER would use it. Mod loaders still
+    // need a version to be picked.
+    fn get_highlighted(
+        &mut self,
+    ) -> Option<(MinVersion, Box<dyn ModLoaderInstaller>, Option<String>)> {
+        if self.selected_mod_loader.is_some() {
+            match (&self.selected, &self.selected_mod_loader) {
+                (Some(version), Some(mod_loader)) => Some((
+                    version.clone(),
+                    mod_loader.clone_instance(),
+                    Some(self.loader_version_list.selected()?.clone()),
+                )),
+                _ => None,
+            }
+        } else if let Some(version) = &self.selected {
+            match self.loader_list.selected() {
+                Some(mod_loader) if mod_loader.is_vanilla() => {
+                    Some((version.clone(), mod_loader.clone_instance(), None))
+                }
+                _ => None,
+            }
+        } else {
+            self.mc_version_table
+                .items
+                .get(self.mc_version_table.state.selected().unwrap_or(0))
+                .map(|version| {
+                    (
+                        version.clone(),
+                        self.modding_handler.vanilla.clone_instance(),
+                        None,
+                    )
+                })
+        }
+    }
+
+    fn create_instance(&mut self) -> Action {
+        let created = match self.get_highlighted() {
+            None => return Action::None,
+            Some((version, mod_loader, loader_version)) => {
+                instance::create_instance(version.id, mod_loader.as_ref(), loader_version)
+            }
+        };
+
+        match created {
+            Ok(_) => Action::NextTab(Tab::Instance),
+            Err(err) => {
+                self.message = err;
+                Action::None
+            }
+        }
+    }
+
+    // Installs the highlighted entry like a launch, then writes its launch script.
+    fn export(&mut self, include_token: bool) -> Action {
+        match self.get_highlighted() {
+            None => Action::None,
+            Some((version, mod_loader, loader_version)) => Action::NextTab(Tab::Download(
+                version,
+                self.versions.clone(),
+                mod_loader,
+                loader_version,
+                None,
+                LaunchMode::Export(include_token),
+            )),
+        }
+    }
+
     pub fn select(&mut self) {
         if self.selected_mod_loader.is_some() {
             match self.loader_version_list.selected() {
@@ -182,9 +342,21 @@ impl VersionTab {
 
 impl TabTrait for VersionTab {
     fn render(&mut self, f: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let recent_height = match self.recent.len() {
+            0 => 0,
+            len => len as u16 + 2,
+        };
+        let message_height = match self.message.is_empty() {
+            true => 0,
+            false => 1,
+        };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Ratio(1, 1)])
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(recent_height),
+                Constraint::Length(message_height),
+            ])
             .split(area);
 
         if self.selected_mod_loader.is_some() {
@@ -194,9 +366,19 @@ impl TabTrait for VersionTab {
         } else {
             self.render_version_list(f, chunks[0]);
         }
+        if recent_height > 0 {
+            self.render_recent_list(f, chunks[1]);
+        }
+        if message_height > 0 {
+            let message = Paragraph::new(vec![Spans::from(self.message.clone())])
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+            f.render_widget(message, chunks[2]);
+        }
     }
 
     fn on_key_press(&mut self, key_code: KeyCode) -> Action {
+        self.message = String::new();
         match key_code {
             KeyCode::Enter => {
                 self.select();
@@ -217,6 +399,8 @@ impl TabTrait for VersionTab {
                                     self.versions.clone(),
                                     mod_loader.clone_instance(),
                                     None,
+                                    None,
+                                    LaunchMode::Play,
                                 ))
                             } else {
                                 self.build_mod_loader_version_list();
@@ -229,6 +413,8 @@ impl TabTrait for VersionTab {
                         self.versions.clone(),
                         self.selected_mod_loader.take().unwrap(),
                         Some(mod_loader_version.clone()),
+                        None,
+                        LaunchMode::Play,
                     )),
                 }
             }
@@ -273,6 +459,20 @@ impl TabTrait for VersionTab {
                 self.build_table_state();
                 Action::None
             }
+            KeyCode::Char('r') => Action::NextTab(Tab::Runtime),
+            KeyCode::Char('i') => self.create_instance(),
+            KeyCode::Char('n') => Action::NextTab(Tab::Instance),
+            KeyCode::Char('e') => self.export(false),
+            KeyCode::Char('E') => self.export(true),
+            KeyCode::Char('p') => match self
+                .mc_version_table
+                .items
+                .get(self.mc_version_table.state.selected().unwrap_or(0))
+            {
+                None => Action::None,
+                Some(version) => Action::NextTab(Tab::Profile(version.id.clone())),
+            },
+            KeyCode::Char(c @ '1'..='5') => self.relaunch(c as usize - '1' as usize),
             _ => Action::None,
         }
     }
@@ -302,6 +502,32 @@ impl TabTrait for VersionTab {
             String::from("Show/Hide old betas and alphas"),
             self.old,
         ));
+        vec.push(TabBinding::Default(
+            String::from("R"),
+            String::from("Manage Java runtimes"),
+        ));
+        vec.push(TabBinding::Default(
+            String::from("P"),
+            String::from("Edit profile of selected version"),
+        ));
+        vec.push(TabBinding::Default(
+            String::from("I"),
+            String::from("Create an instance from the selection"),
+        ));
+        vec.push(TabBinding::Default(
+            String::from("N"),
+            String::from("Show instances"),
+        ));
+        vec.push(TabBinding::Default(
+            String::from("E"),
+            String::from("Install and export a launch script, SHIFT+E with the token"),
+        ));
+        if !self.recent.is_empty() {
+            vec.push(TabBinding::Default(
+                format!("1-{}", self.recent.len()),
+                String::from("Relaunch a recently played entry"),
+            ));
+        }
 
         vec
     }
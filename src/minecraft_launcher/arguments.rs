@@ -1,15 +1,26 @@
+use crate::minecraft_launcher::log4shell::Mitigation;
+use crate::minecraft_launcher::manifest::launcher_profiles::Profile;
 use crate::minecraft_launcher::manifest::version;
 use crate::minecraft_launcher::manifest::version::{Either, Os, RuleAction};
 use crate::minecraft_launcher::path;
+use chrono::Local;
 use os_info::{get as get_os_info, Version};
 
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::env::consts;
 use std::ops::Add;
+use std::path::PathBuf;
 
 pub fn get_args_from_manifest(
     version: &version::Main,
     options: &LaunchOptions,
 ) -> Option<Vec<String>> {
+    let log4j_args = &options.log4j_mitigation;
+
+    let features = options.get_features();
+    let platform = Platform::current();
+
     match version.clone().arguments {
         None => match version.clone().minecraft_arguments {
             None => None,
@@ -18,15 +29,38 @@ pub fn get_args_from_manifest(
                     "-Djava.library.path=${natives_directory}".to_string(),
                     "-cp".to_string(),
                     "${classpath}".to_string(),
-                    version.clone().main_class,
                 ];
 
+                if let Some((_, args)) = log4j_args {
+                    command.extend(args.clone());
+                }
+                command.extend(get_debug_args(options));
+                command.extend(options.jvm_args.clone());
+                command.push(version.clone().main_class);
+
                 let arguments: Vec<&str> = minecraft_arguments.split(' ').collect();
 
                 for argument in arguments {
                     command.push(argument.to_string());
                 }
 
+                // Legacy arguments have no rules, so these are added by hand.
+                if options.demo {
+                    command.push("--demo".to_string());
+                }
+                if options.custom_resolution {
+                    command.push("--width".to_string());
+                    command.push("${resolution_width}".to_string());
+                    command.push("--height".to_string());
+                    command.push("${resolution_height}".to_string());
+                }
+                if options.fullscreen {
+                    command.push("--fullscreen".to_string());
+                }
+                command.extend(get_legacy_quick_play_args(options));
+
+                command.extend(options.game_args.clone());
+
                 Some(command)
             }
         },
@@ -46,7 +80,7 @@ pub fn get_args_from_manifest(
                                 command.push(string);
                             }
                             Either::Right(custom_arg) => {
-                                match match_rules(custom_arg.rules, Some(options)) {
+                                match match_rules(&custom_arg.rules, &features, &platform) {
                                     RuleAction::Allow => match custom_arg.value {
                                         Either::Left(strin) => {
                                             command.push(strin);
@@ -65,7 +99,11 @@ pub fn get_args_from_manifest(
                 }
             };
 
-            match version.clone().logging {
+            let replaces_config = log4j_args
+                .as_ref()
+                .map_or(false, |(mitigation, _)| mitigation.replaces_config());
+
+            match version.clone().logging.filter(|_| !replaces_config) {
                 None => {}
                 Some(logging) => match logging.client {
                     None => {}
@@ -88,6 +126,11 @@ pub fn get_args_from_manifest(
                 },
             }
 
+            if let Some((_, args)) = log4j_args {
+                command.extend(args.clone());
+            }
+            command.extend(get_debug_args(options));
+            command.extend(options.jvm_args.clone());
             command.push(version.clone().main_class);
 
             for i in arguments.game {
@@ -96,7 +139,7 @@ pub fn get_args_from_manifest(
                         command.push(string);
                     }
                     Either::Right(custom_arg) => {
-                        match match_rules(custom_arg.rules, Some(options)) {
+                        match match_rules(&custom_arg.rules, &features, &platform) {
                             RuleAction::Allow => match custom_arg.value {
                                 Either::Left(strin) => {
                                     command.push(strin);
@@ -113,6 +156,17 @@ pub fn get_args_from_manifest(
                 };
             }
 
+            if options.fullscreen {
+                command.push("--fullscreen".to_string());
+            }
+
+            // Versions older than 1.20 don't know about Quick Play.
+            if !command.iter().any(|arg| arg.starts_with("--quickPlay")) {
+                command.extend(get_legacy_quick_play_args(options));
+            }
+
+            command.extend(options.game_args.clone());
+
             Some(command)
         }
     }
@@ -123,43 +177,24 @@ pub fn get_natives(version: &version::Main) -> String {
 
     let version = version.clone();
 
-    let separator = match get_os() {
-        Os::Windows => ";",
-        _ => ":",
-    };
+    let separator = get_classpath_separator();
+    let platform = Platform::current();
 
     for library in version.libraries {
-        let allowed = match library.rules {
+        let allowed = match &library.rules {
             None => RuleAction::Allow,
-            Some(rules) => match_rules(rules, None),
+            Some(rules) => match_rules(rules, &HashSet::new(), &platform),
         };
 
         match allowed {
             RuleAction::Allow => {
-                let name_parts: Vec<&str> = library.name.split(':').collect();
-
-                let lib_path_temp = *name_parts.get(0).unwrap_or(&"");
-                let lib_path_temp = lib_path_temp.replace(".", "/");
-                let lib_path = lib_path_temp.as_str();
-                let name = *name_parts.get(1).unwrap_or(&"");
-                let version = *name_parts.get(2).unwrap_or(&"");
-
-                let file_name = match library.natives {
-                    None => format!("{}-{}", name, version),
-                    Some(natives) => match natives.get(get_os().to_str().as_str()) {
-                        None => format!("{}-{}", name, version),
-                        Some(native) => format!("{}-{}-{}", name, version, native),
-                    },
-                };
-
-                match path::get_library_path(&format!(
-                    "{}/{}/{}/{}.jar",
-                    lib_path, name, version, file_name
-                )) {
-                    None => {}
-                    Some(lib_path) => {
-                        native_arg =
-                            native_arg.add(format!("{}{}", lib_path.display(), separator).as_str());
+                for library_path in get_library_paths(&library) {
+                    match path::get_library_path(&library_path) {
+                        None => {}
+                        Some(lib_path) => {
+                            native_arg = native_arg
+                                .add(format!("{}{}", lib_path.display(), separator).as_str());
+                        }
                     }
                 }
             }
@@ -179,91 +214,208 @@ pub fn get_natives(version: &version::Main) -> String {
     native_arg
 }
 
-pub fn match_rules(rules: Vec<version::Rule>, options: Option<&LaunchOptions>) -> RuleAction {
-    let mut val: RuleAction = RuleAction::Allow;
+// Where install_libraries put the jars: the download paths when the manifest has them, the
+// maven layout otherwise.
+fn get_library_paths(library: &version::Library) -> Vec<String> {
+    let native = library
+        .natives
+        .as_ref()
+        .and_then(|natives| natives.get(get_os().to_str().as_str()));
 
-    for rule in rules {
-        if rule.features.is_some() {
-            let mut mat = true;
-            for i in rule.features.expect("Wut") {
-                mat = match i.0.as_str() {
-                    "is_demo_user" => match options {
-                        None => false,
-                        Some(opt) => opt.demo,
-                    },
-                    "has_custom_resolution" => match options {
-                        None => false,
-                        Some(opt) => opt.custom_resolution,
-                    },
-                    _ => false,
-                };
-                if !mat {
-                    break;
-                }
-            }
-            if mat {
-                val = rule.action;
-            } else {
-                val = match rule.action {
-                    RuleAction::Allow => RuleAction::Disallow,
-                    RuleAction::Disallow => RuleAction::Allow,
-                }
-            }
-        } else if rule.os.is_some() {
-            let mut mat = true;
-            for i in rule.os.expect("Wut") {
-                if !mat {
-                    break;
-                }
-                mat = match i.0.as_str() {
-                    "name" => i.1 == get_os().to_str(),
-                    "version" => match get_os_info().version() {
-                        Version::Unknown => false,
-                        Version::Semantic(maj, min, pat) => {
-                            let mut v = i.1.replace("\\", "").replace("d$", "0");
-
-                            let mut sup_or_equ = false;
-                            if v.starts_with('^') {
-                                sup_or_equ = true;
-                                v = v.replace("^", "");
-                            }
+    if let Some(downloads) = &library.downloads {
+        let mut paths = vec![];
 
-                            match Version::from_string(v) {
-                                Version::Unknown => false,
-                                Version::Semantic(major, minor, patch) => {
-                                    if sup_or_equ {
-                                        major > *maj
-                                            || major == *maj
-                                                && (minor > *min || minor == *min && patch >= *pat)
-                                    } else {
-                                        *maj == major && *min == minor && *pat == patch
-                                    }
-                                }
-                                Version::Rolling(_) => false,
-                                Version::Custom(_) => false,
-                            }
-                        }
-                        Version::Rolling(_) => false,
-                        Version::Custom(_) => false,
-                    },
-                    "arch" => i.1 == consts::ARCH,
-                    _ => false,
-                };
-            }
-            if mat {
-                val = rule.action;
-            } else {
-                val = match rule.action {
-                    RuleAction::Allow => RuleAction::Disallow,
-                    RuleAction::Disallow => RuleAction::Allow,
+        if let Some(artifact) = &downloads.artifact {
+            paths.push(artifact.path.clone());
+        }
+        if let Some(classifier) = native.and_then(|native| {
+            downloads
+                .classifiers
+                .as_ref()
+                .and_then(|classifiers| classifiers.get(native))
+        }) {
+            paths.push(classifier.path.clone());
+        }
+
+        if !paths.is_empty() {
+            return paths;
+        }
+    }
+
+    let name_parts: Vec<&str> = library.name.split(':').collect();
+
+    let group = name_parts.get(0).unwrap_or(&"").replace(".", "/");
+    let name = *name_parts.get(1).unwrap_or(&"");
+    let version = *name_parts.get(2).unwrap_or(&"");
+
+    let file_name = match native {
+        None => format!("{}-{}", name, version),
+        Some(native) => format!("{}-{}-{}", name, version, native),
+    };
+
+    vec![format!("{}/{}/{}/{}.jar", group, name, version, file_name)]
+}
+
+/// The system as manifest rules see it.
+pub struct Platform {
+    pub name: String,
+    pub version: String,
+    pub arch: String,
+}
+
+impl Platform {
+    pub fn current() -> Platform {
+        Platform {
+            name: get_os().to_str(),
+            version: match get_os_info().version() {
+                Version::Semantic(major, minor, patch) => {
+                    format!("{}.{}.{}", major, minor, patch)
                 }
-            }
-        } else {
-            val = rule.action;
-        };
+                Version::Rolling(Some(version)) | Version::Custom(version) => version.clone(),
+                Version::Rolling(None) | Version::Unknown => String::new(),
+            },
+            arch: get_arch().to_string(),
+        }
     }
+}
+
+/// Mojang's names for the architecture, the ones Java reports in `os.arch`.
+pub fn get_arch() -> &'static str {
+    match consts::ARCH {
+        "x86" => "x86",
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        arch => arch,
+    }
+}
+
+/// Rules are read in order, the last one matching gives the action. Without any matching
+/// rule the element is disallowed, without rules at all it is allowed. The platform is read
+/// once by the callers, `Platform::current` is slow.
+pub fn match_rules(
+    rules: &[version::Rule],
+    features: &HashSet<String>,
+    platform: &Platform,
+) -> RuleAction {
+    if rules.is_empty() {
+        return RuleAction::Allow;
+    }
+
+    let mut action = RuleAction::Disallow;
+
+    for rule in rules {
+        if is_rule_matching(rule, features, platform) {
+            action = rule.action.clone();
+        }
+    }
+
+    action
+}
+
+fn is_rule_matching(rule: &version::Rule, features: &HashSet<String>, platform: &Platform) -> bool {
+    let os_matches = match &rule.os {
+        None => true,
+        Some(os) => os.iter().all(|(key, value)| match key.as_str() {
+            "name" => *value == platform.name,
+            // Mojang matches the version with a regex, "^10\\." for Windows 10 for example.
+            "version" => Regex::new(value).map_or(false, |regex| regex.is_match(&platform.version)),
+            "arch" => *value == platform.arch,
+            _ => false,
+        }),
+    };
+
+    let features_match = match &rule.features {
+        None => true,
+        Some(rule_features) => rule_features
+            .iter()
+            .all(|(feature, enabled)| features.contains(feature) == *enabled),
+    };
+
+    os_matches && features_match
+}
+
+// JVM options, so they go before the main class like the profile's ones.
+fn get_debug_args(options: &LaunchOptions) -> Vec<String> {
+    let mut args = vec![];
+
+    for agent in &options.java_agents {
+        args.push(format!("-javaagent:{}", agent));
+    }
+
+    // A bare port listens on every interface with Java 8 and on localhost only after.
+    if let Some(port) = options.debug_port {
+        args.push(format!(
+            "-agentlib:jdwp=transport=dt_socket,server=y,suspend={},address={}",
+            if options.debug_suspend { "y" } else { "n" },
+            port
+        ));
+    }
+
+    if let Some(recording) = &options.flight_recording {
+        // Oracle's Java 8, which jre-legacy is, only records once commercial features are on.
+        if options.java_major_version == 8 {
+            args.push(String::from("-XX:+UnlockCommercialFeatures"));
+        }
+        args.push(format!(
+            "-XX:StartFlightRecording=dumponexit=true,filename={}",
+            recording.display()
+        ));
+    }
+
+    args
+}
+
+// `--server` and `--port` only allow joining servers.
+fn get_legacy_quick_play_args(options: &LaunchOptions) -> Vec<String> {
+    match &options.quick_play {
+        Some(QuickPlay::Multiplayer(address)) => {
+            let (host, port) = split_server_address(address);
+            vec![
+                "--server".to_string(),
+                host,
+                "--port".to_string(),
+                port.to_string(),
+            ]
+        }
+        _ => vec![],
+    }
+}
+
+/// Warns when the quick play target of the profile can't be passed to the game, versions
+/// older than 1.20 can only join servers.
+pub fn get_quick_play_warning(args: &[String], options: &LaunchOptions) -> Option<String> {
+    let target = match &options.quick_play {
+        Some(QuickPlay::Singleplayer(world)) => format!("world '{}'", world),
+        Some(QuickPlay::Realms(realm)) => format!("realm '{}'", realm),
+        _ => return None,
+    };
+
+    if args.iter().any(|arg| arg.starts_with("--quickPlay")) {
+        None
+    } else {
+        Some(format!(
+            "Version {} doesn't support quick play into the {}, starting on the title screen",
+            options.version, target
+        ))
+    }
+}
+
+fn split_server_address(address: &str) -> (String, u16) {
+    match address.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => (host.to_string(), port),
+            Err(_) => (address.to_string(), 25565),
+        },
+        None => (address.to_string(), 25565),
+    }
+}
 
-    val
+pub fn get_classpath_separator() -> &'static str {
+    match get_os() {
+        Os::Windows => ";",
+        _ => ":",
+    }
 }
 
 pub fn get_os() -> Os {
@@ -274,6 +426,28 @@ pub fn get_os() -> Os {
     }
 }
 
+#[derive(Clone)]
+pub enum QuickPlay {
+    Singleplayer(String),
+    Multiplayer(String),
+    Realms(String),
+}
+
+/// The player the game is launched for, as the authentication server described them.
+#[derive(Clone)]
+pub struct Account {
+    pub name: String,
+    pub uuid: String,
+    pub access_token: String,
+    pub user_type: String,
+    pub client_id: String,
+    pub xuid: String,
+    // JSON map of the account properties, "{}" when there are none.
+    pub user_properties: String,
+    // Accounts without a profile don't own the game.
+    pub demo: bool,
+}
+
 #[derive(Clone)]
 pub struct LaunchOptions {
     pub natives_directory: String,
@@ -287,21 +461,37 @@ pub struct LaunchOptions {
     pub player_token: String,
     pub user_type: String,
     pub version_type: String,
+    // Only Microsoft accounts have these, the game accepts them empty.
+    pub client_id: String,
+    pub xuid: String,
+    // JSON map of the account properties, like the old Twitch token.
+    pub user_properties: String,
+    pub library_directory: String,
+    pub primary_jar: String,
     pub demo: bool,
     pub custom_resolution: bool,
     pub width: Option<String>,
     pub height: Option<String>,
+    pub fullscreen: bool,
+    pub quick_play: Option<QuickPlay>,
+    pub debug_port: Option<u16>,
+    pub debug_suspend: bool,
+    pub flight_recording: Option<PathBuf>,
+    pub java_major_version: u8,
+    pub java_agents: Vec<String>,
+    // Log4Shell mitigation whose config was written, with its JVM arguments.
+    pub log4j_mitigation: Option<(Mitigation, Vec<String>)>,
+    pub jvm_args: Vec<String>,
+    pub game_args: Vec<String>,
 }
 
 impl LaunchOptions {
     pub fn from_version(
         version: &version::Main,
-        player_name: String,
-        player_uuid: String,
-        player_token: String,
-        user_type: String,
-    ) -> Result<LaunchOptions, &str> {
+        account: &Account,
+    ) -> Result<LaunchOptions, &'static str> {
         let version = version.clone();
+        let java_major_version = version.get_java_major_version();
 
         let natives_directory = match path::get_bin_folder(version.id.clone()) {
             None => {
@@ -331,72 +521,534 @@ impl LaunchOptions {
 
         let version_type = version._type.to_string();
 
+        let library_directory = path::get_minecraft_directory()
+            .join("libraries")
+            .display()
+            .to_string();
+
+        let primary_jar = match path::get_version_folder(&version.id) {
+            None => {
+                return Err("Unable to get version folder!");
+            }
+            Some(folder) => folder
+                .join(format!("{}.jar", version.id))
+                .display()
+                .to_string(),
+        };
+
         Ok(LaunchOptions {
             natives_directory,
             classpath,
-            player_name,
+            player_name: account.name.clone(),
             version: version.id,
             game_directory,
             assets_directory,
             assets_index,
-            player_uuid,
-            player_token,
-            user_type,
+            player_uuid: account.uuid.clone(),
+            player_token: account.access_token.clone(),
+            user_type: account.user_type.clone(),
             version_type,
-            demo: false,
+            client_id: account.client_id.clone(),
+            xuid: account.xuid.clone(),
+            user_properties: account.user_properties.clone(),
+            library_directory,
+            primary_jar,
+            demo: account.demo,
             custom_resolution: false,
             width: None,
             height: None,
+            fullscreen: false,
+            quick_play: None,
+            debug_port: None,
+            debug_suspend: false,
+            flight_recording: None,
+            java_major_version,
+            java_agents: Vec::new(),
+            log4j_mitigation: None,
+            jvm_args: Vec::new(),
+            game_args: Vec::new(),
         })
     }
 
-    pub fn fill_argument_list(&mut self, args: Vec<String>) -> Vec<String> {
-        let mut new_args: Vec<String> = Vec::new();
-
-        for arg in args {
-            let mut arg = arg.replace("${natives_directory}", self.natives_directory.as_str());
-            arg = arg.replace("${launcher_name}", "Rusty-Craft");
-            arg = arg.replace("${launcher_version}", crate::get_version().as_str());
-            arg = arg.replace("${classpath}", self.classpath.as_str());
-            arg = arg.replace("${auth_player_name}", self.player_name.as_str());
-            arg = arg.replace("${version_name}", "Rusty-Craft");
-            arg = arg.replace("${game_directory}", self.game_directory.as_str());
-            arg = arg.replace("${assets_root}", self.assets_directory.as_str());
-            arg = arg.replace("${assets_index_name}", self.assets_index.as_str());
-            arg = arg.replace("${auth_uuid}", self.player_uuid.as_str());
-            arg = arg.replace("${auth_access_token}", self.player_token.as_str());
-            arg = arg.replace("${user_type}", self.user_type.as_str());
-            arg = arg.replace("${version_type}", self.version_type.as_str());
-            arg = arg.replace("${user_properties}", "{}");
-            match self.width.clone() {
-                None => {
-                    arg = arg.replace("${resolution_width}", "854");
-                }
-                Some(width) => {
-                    arg = arg.replace("${resolution_width}", width.as_str());
-                }
+    /// Adds the memory settings and extra arguments of the profile, they go through
+    /// `fill_argument_list` like the manifest ones.
+    pub fn apply_profile(&mut self, profile: &Profile) -> Result<(), String> {
+        self.jvm_args.clear();
+        self.game_args.clear();
+
+        if let Some(game_dir) = &profile.game_dir {
+            self.game_directory = game_dir.clone();
+        }
+
+        if let Some(java_args) = &profile.java_args {
+            self.jvm_args.extend(split_arguments(java_args));
+        }
+
+        // Added after javaArgs so that they win over its -Xmx, the JVM keeps the last one.
+        if let Some(settings) = &profile.rusty_craft {
+            check_memory(
+                settings.min_memory.as_deref(),
+                settings.max_memory.as_deref(),
+            )?;
+            if let Some(min_memory) = &settings.min_memory {
+                self.jvm_args.push(format!("-Xms{}", min_memory));
             }
-            match self.height.clone() {
-                None => {
-                    arg = arg.replace("${resolution_height}", "480");
+            if let Some(max_memory) = &settings.max_memory {
+                self.jvm_args.push(format!("-Xmx{}", max_memory));
+            }
+            if let Some(game_args) = &settings.game_args {
+                self.game_args.extend(split_arguments(game_args));
+            }
+        }
+
+        match &profile.resolution {
+            None => {
+                self.custom_resolution = false;
+                self.width = None;
+                self.height = None;
+            }
+            Some(resolution) => {
+                self.custom_resolution = true;
+                self.width = Some(resolution.width.to_string());
+                self.height = Some(resolution.height.to_string());
+            }
+        }
+
+        self.fullscreen = profile
+            .rusty_craft
+            .as_ref()
+            .and_then(|settings| settings.fullscreen)
+            .unwrap_or(false);
+
+        let settings = profile.rusty_craft.clone().unwrap_or_default();
+        self.debug_port = settings.debug_port;
+        self.debug_suspend = settings.debug_suspend.unwrap_or(false);
+        self.java_agents = settings
+            .java_agents
+            .as_deref()
+            .map(split_arguments)
+            .unwrap_or_default();
+        // One file per launch, next to the saves of the instance.
+        self.flight_recording = match settings.flight_recorder {
+            Some(true) => Some(
+                PathBuf::from(&self.game_directory)
+                    .join("recordings")
+                    .join(format!("{}.jfr", Local::now().format("%Y-%m-%d_%H.%M.%S"))),
+            ),
+            _ => None,
+        };
+        // The recorder splits its options on commas, a path can't hold one.
+        if let Some(recording) = &self.flight_recording {
+            if recording.to_string_lossy().contains(',') {
+                return Err(format!(
+                    "Flight recordings can't be saved to {}, the path contains a comma",
+                    recording.display()
+                ));
+            }
+        }
+
+        self.quick_play = profile.rusty_craft.as_ref().and_then(|settings| {
+            match (&settings.world, &settings.server, &settings.realm) {
+                (Some(world), _, _) => Some(QuickPlay::Singleplayer(world.clone())),
+                (None, Some(server), _) => Some(QuickPlay::Multiplayer(server.clone())),
+                (None, None, Some(realm)) => Some(QuickPlay::Realms(realm.clone())),
+                _ => None,
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Every placeholder used by the vanilla, Forge and NeoForge manifests, by name.
+    pub fn get_placeholders(&self) -> HashMap<&'static str, String> {
+        let mut placeholders = HashMap::new();
+
+        placeholders.insert("natives_directory", self.natives_directory.clone());
+        placeholders.insert("launcher_name", String::from("Rusty-Craft"));
+        placeholders.insert("launcher_version", crate::get_version());
+        placeholders.insert("classpath", self.classpath.clone());
+        placeholders.insert("classpath_separator", get_classpath_separator().to_string());
+        placeholders.insert("library_directory", self.library_directory.clone());
+        placeholders.insert("primary_jar", self.primary_jar.clone());
+        placeholders.insert("auth_player_name", self.player_name.clone());
+        placeholders.insert("version_name", self.version.clone());
+        placeholders.insert("game_directory", self.game_directory.clone());
+        placeholders.insert("assets_root", self.assets_directory.clone());
+        placeholders.insert("assets_index_name", self.assets_index.clone());
+        placeholders.insert(
+            "game_assets",
+            path::get_minecraft_directory()
+                .join("resources")
+                .display()
+                .to_string(),
+        );
+        placeholders.insert("auth_uuid", self.player_uuid.clone());
+        placeholders.insert("auth_access_token", self.player_token.clone());
+        // Versions before 1.7 take the token this way.
+        placeholders.insert("auth_session", self.player_token.clone());
+        placeholders.insert("auth_xuid", self.xuid.clone());
+        placeholders.insert("clientid", self.client_id.clone());
+        placeholders.insert("user_type", self.user_type.clone());
+        placeholders.insert("user_properties", self.user_properties.clone());
+        placeholders.insert("version_type", self.version_type.clone());
+        placeholders.insert(
+            "resolution_width",
+            self.width.clone().unwrap_or_else(|| String::from("854")),
+        );
+        placeholders.insert(
+            "resolution_height",
+            self.height.clone().unwrap_or_else(|| String::from("480")),
+        );
+        placeholders.insert(
+            "quickPlayPath",
+            PathBuf::from(&self.game_directory)
+                .join("quickPlay")
+                .join("log.json")
+                .display()
+                .to_string(),
+        );
+        match &self.quick_play {
+            None => {}
+            Some(QuickPlay::Singleplayer(world)) => {
+                placeholders.insert("quickPlaySingleplayer", world.clone());
+            }
+            Some(QuickPlay::Multiplayer(address)) => {
+                placeholders.insert("quickPlayMultiplayer", address.clone());
+            }
+            Some(QuickPlay::Realms(realm)) => {
+                placeholders.insert("quickPlayRealms", realm.clone());
+            }
+        }
+
+        placeholders
+    }
+
+    /// Feature flags of the argument rules enabled by these options.
+    pub fn get_features(&self) -> HashSet<String> {
+        let mut features = HashSet::new();
+
+        if self.demo {
+            features.insert(String::from("is_demo_user"));
+        }
+        if self.custom_resolution {
+            features.insert(String::from("has_custom_resolution"));
+        }
+        match &self.quick_play {
+            None => {}
+            Some(QuickPlay::Singleplayer(_)) => {
+                features.insert(String::from("is_quick_play_singleplayer"));
+            }
+            Some(QuickPlay::Multiplayer(_)) => {
+                features.insert(String::from("is_quick_play_multiplayer"));
+            }
+            Some(QuickPlay::Realms(_)) => {
+                features.insert(String::from("is_quick_play_realms"));
+            }
+        }
+
+        features
+    }
+
+    /// Same placeholders as the arguments, for values like the environment variables.
+    pub fn fill_placeholders(&self, value: &str) -> String {
+        fill_placeholders(value, &self.get_placeholders(), &mut vec![])
+    }
+
+    /// Unknown placeholders are left as they are, their names are returned with the arguments.
+    pub fn fill_argument_list(&mut self, args: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let placeholders = self.get_placeholders();
+        let mut unresolved = vec![];
+
+        let args = args
+            .iter()
+            .map(|arg| fill_placeholders(arg, &placeholders, &mut unresolved))
+            .collect();
+
+        (args, unresolved)
+    }
+}
+
+// Values are never scanned again, a path containing "${" stays as it is. Unknown names are added
+// to `unresolved`, each only once.
+fn fill_placeholders(
+    arg: &str,
+    placeholders: &HashMap<&'static str, String>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    let mut filled = String::new();
+    let mut rest = arg;
+
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start + 2..].find('}') {
+            None => break,
+            Some(end) => start + 2 + end,
+        };
+
+        let name = &rest[start + 2..end];
+        filled.push_str(&rest[..start]);
+        match placeholders.get(name) {
+            None => {
+                filled.push_str(&rest[start..=end]);
+                if !unresolved.iter().any(|known| known == name) {
+                    unresolved.push(name.to_string());
                 }
-                Some(height) => {
-                    arg = arg.replace("${resolution_height}", height.as_str());
+            }
+            Some(value) => filled.push_str(value),
+        }
+        rest = &rest[end + 1..];
+    }
+    filled.push_str(rest);
+
+    filled
+}
+
+/// Memory sizes as the JVM accepts them: "512M", "4G", "2048m"... in bytes.
+fn parse_memory(memory: &str) -> Option<u64> {
+    let digits = memory.trim_end_matches(|c| "kKmMgG".contains(c));
+    if digits.is_empty() || memory.len() - digits.len() > 1 {
+        return None;
+    }
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let unit = match memory[digits.len()..].to_ascii_lowercase().as_str() {
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        _ => 1,
+    };
+    digits.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// Checks the memory settings of a profile, the JVM refuses to start when the minimum is
+/// above the maximum.
+pub fn check_memory(min_memory: Option<&str>, max_memory: Option<&str>) -> Result<(), String> {
+    let parse = |memory: Option<&str>| match memory {
+        None => Ok(None),
+        Some(memory) => match parse_memory(memory) {
+            Some(bytes) => Ok(Some(bytes)),
+            None => Err(format!(
+                "Invalid memory size '{}', use something like 512M or 4G",
+                memory
+            )),
+        },
+    };
+
+    match (parse(min_memory)?, parse(max_memory)?) {
+        (Some(min), Some(max)) if min > max => Err(format!(
+            "Minimum memory {} is above the maximum memory {}",
+            min_memory.unwrap_or_default(),
+            max_memory.unwrap_or_default()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Splits user provided arguments on whitespace, double quotes keep spaces together.
+pub fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut split: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in arguments.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    split.push(current.clone());
+                    current.clear();
                 }
             }
-            arg = arg.replace(
-                "${game_assets}",
-                path::get_minecraft_directory()
-                    .join("resources")
-                    .display()
-                    .to_string()
-                    .as_str(),
+            c => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        split.push(current);
+    }
+
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform(name: &str, version: &str, arch: &str) -> Platform {
+        Platform {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: arch.to_string(),
+        }
+    }
+
+    fn features(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn is_allowed(rules: &str, features: &HashSet<String>, platform: &Platform) -> bool {
+        let rules: Vec<version::Rule> = serde_json::from_str(rules).unwrap();
+        matches!(match_rules(&rules, features, platform), RuleAction::Allow)
+    }
+
+    // Rules as they appear in Mojang's version manifests.
+    const NO_RULES: &str = "[]";
+    const LWJGL_NOT_OSX: &str =
+        r#"[{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]"#;
+    const LWJGL_OSX_ONLY: &str = r#"[{"action": "allow", "os": {"name": "osx"}}]"#;
+    const WINDOWS_10: &str =
+        r#"[{"action": "allow", "os": {"name": "windows", "version": "^10\\."}}]"#;
+    const X86_STACK_SIZE: &str = r#"[{"action": "allow", "os": {"arch": "x86"}}]"#;
+    const DEMO_USER: &str = r#"[{"action": "allow", "features": {"is_demo_user": true}}]"#;
+    const CUSTOM_RESOLUTION: &str =
+        r#"[{"action": "allow", "features": {"has_custom_resolution": true}}]"#;
+    const QUICK_PLAY_MULTIPLAYER: &str =
+        r#"[{"action": "allow", "features": {"is_quick_play_multiplayer": true}}]"#;
+    const INVALID_VERSION_REGEX: &str =
+        r#"[{"action": "allow", "os": {"name": "linux", "version": "^(10"}}]"#;
+
+    #[test]
+    fn os_rules() {
+        let linux = platform("linux", "5.15.0", "x64");
+        let windows_10 = platform("windows", "10.0.19045", "x64");
+        let windows_7 = platform("windows", "6.1.7601", "x86");
+        let macos = platform("osx", "12.6.0", "arm64");
+
+        let cases = [
+            (NO_RULES, &linux, true),
+            (LWJGL_NOT_OSX, &linux, true),
+            (LWJGL_NOT_OSX, &windows_10, true),
+            (LWJGL_NOT_OSX, &macos, false),
+            (LWJGL_OSX_ONLY, &linux, false),
+            (LWJGL_OSX_ONLY, &windows_7, false),
+            (LWJGL_OSX_ONLY, &macos, true),
+            (WINDOWS_10, &windows_10, true),
+            (WINDOWS_10, &windows_7, false),
+            (WINDOWS_10, &linux, false),
+            (X86_STACK_SIZE, &windows_7, true),
+            (X86_STACK_SIZE, &windows_10, false),
+            (X86_STACK_SIZE, &macos, false),
+            (INVALID_VERSION_REGEX, &linux, false),
+        ];
+
+        for (i, (rules, platform, expected)) in cases.iter().enumerate() {
+            assert_eq!(
+                is_allowed(rules, &HashSet::new(), platform),
+                *expected,
+                "case {}: {} on {} {} {}",
+                i,
+                rules,
+                platform.name,
+                platform.version,
+                platform.arch
             );
-            arg = arg.replace("${auth_session}", self.player_token.as_str());
+        }
+    }
+
+    #[test]
+    fn feature_rules() {
+        let linux = platform("linux", "5.15.0", "x64");
 
-            new_args.push(arg);
+        let cases = [
+            (DEMO_USER, features(&[]), false),
+            (DEMO_USER, features(&["is_demo_user"]), true),
+            (DEMO_USER, features(&["has_custom_resolution"]), false),
+            (
+                CUSTOM_RESOLUTION,
+                features(&["has_custom_resolution"]),
+                true,
+            ),
+            (
+                CUSTOM_RESOLUTION,
+                features(&["is_demo_user", "has_custom_resolution"]),
+                true,
+            ),
+            (
+                QUICK_PLAY_MULTIPLAYER,
+                features(&["is_quick_play_multiplayer"]),
+                true,
+            ),
+            (
+                QUICK_PLAY_MULTIPLAYER,
+                features(&["is_quick_play_singleplayer"]),
+                false,
+            ),
+            (NO_RULES, features(&["is_demo_user"]), true),
+        ];
+
+        for (i, (rules, features, expected)) in cases.iter().enumerate() {
+            assert_eq!(
+                is_allowed(rules, features, &linux),
+                *expected,
+                "case {}: {} with {:?}",
+                i,
+                rules,
+                features
+            );
         }
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let macos = platform("osx", "12.6.0", "arm64");
+        let rules = r#"[
+            {"action": "disallow", "os": {"name": "osx"}},
+            {"action": "allow", "os": {"name": "osx", "arch": "arm64"}}
+        ]"#;
+
+        assert!(is_allowed(rules, &HashSet::new(), &macos));
+        assert!(!is_allowed(
+            rules,
+            &HashSet::new(),
+            &platform("osx", "10.15.7", "x64")
+        ));
+    }
+
+    #[test]
+    fn memory_checks() {
+        assert!(check_memory(None, None).is_ok());
+        assert!(check_memory(Some("512M"), None).is_ok());
+        assert!(check_memory(Some("1G"), Some("1024m")).is_ok());
+        assert!(check_memory(Some("2048M"), Some("4G")).is_ok());
+        assert!(check_memory(Some("4G"), Some("2048M")).is_err());
+        assert!(check_memory(Some("1g"), Some("1023M")).is_err());
+        assert!(check_memory(Some("4GB"), None).is_err());
+        assert!(check_memory(None, Some("G")).is_err());
+        assert!(check_memory(None, Some("-1G")).is_err());
+    }
+
+    #[test]
+    fn unresolved_placeholders() {
+        let mut placeholders = HashMap::new();
+        placeholders.insert("game_directory", String::from("/home/steve/${instance}"));
+        placeholders.insert("version_name", String::from("1.20.1"));
 
-        new_args
+        let cases = [
+            ("${game_directory}", "/home/steve/${instance}", vec![]),
+            ("--version=${version_name}", "--version=1.20.1", vec![]),
+            (
+                "${resolution_width}",
+                "${resolution_width}",
+                vec!["resolution_width"],
+            ),
+            (
+                "${clientid}-${clientid}-${auth_xuid}",
+                "${clientid}-${clientid}-${auth_xuid}",
+                vec!["clientid", "auth_xuid"],
+            ),
+            ("${unclosed", "${unclosed", vec![]),
+        ];
+
+        for (i, (arg, filled, names)) in cases.iter().enumerate() {
+            let mut unresolved = vec![];
+            assert_eq!(
+                fill_placeholders(arg, &placeholders, &mut unresolved),
+                *filled,
+                "case {}",
+                i
+            );
+            assert_eq!(unresolved, *names, "case {}", i);
+        }
     }
 }
@@ -0,0 +1,69 @@
+use crate::minecraft_launcher::path;
+
+use serde_derive::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Main {
+    #[serde(rename = "preferSystemJava", default)]
+    pub prefer_system_java: bool,
+    #[serde(rename = "javaManifestUrl", default = "default_java_manifest_url")]
+    pub java_manifest_url: String,
+    #[serde(rename = "adoptiumApiUrl", default = "default_adoptium_api_url")]
+    pub adoptium_api_url: String,
+}
+
+impl Default for Main {
+    fn default() -> Self {
+        Main {
+            prefer_system_java: false,
+            java_manifest_url: default_java_manifest_url(),
+            adoptium_api_url: default_adoptium_api_url(),
+        }
+    }
+}
+
+fn default_java_manifest_url() -> String {
+    "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json".to_string()
+}
+
+fn default_adoptium_api_url() -> String {
+    "https://api.adoptium.net".to_string()
+}
+
+/// Reads the launcher config, a missing file gives the defaults.
+pub fn read_config() -> Result<Main, String> {
+    let config_path = path::get_launcher_config_path();
+
+    if !config_path.exists() {
+        return Ok(Main::default());
+    }
+
+    let mut file = match File::open(&config_path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Unable to open launcher config: {}", err)),
+    };
+
+    let mut body = String::new();
+    match file.read_to_string(&mut body) {
+        Ok(_) => match serde_json::from_str(&body) {
+            Ok(config) => Ok(config),
+            Err(err) => Err(format!("Launcher config wrongly formatted! {}", err)),
+        },
+        Err(err) => Err(format!("Unable to read launcher config: {}", err)),
+    }
+}
+
+pub fn write_config(config: &Main) -> Result<(), String> {
+    match serde_json::to_string_pretty(config) {
+        Ok(body) => match File::create(path::get_launcher_config_path()) {
+            Ok(mut file) => match file.write_all(body.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(format!("Unable to write launcher config: {}", err)),
+            },
+            Err(err) => Err(format!("Unable to create launcher config: {}", err)),
+        },
+        Err(err) => Err(format!("Unable to serialize launcher config: {}", err)),
+    }
+}
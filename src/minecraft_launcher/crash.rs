@@ -0,0 +1,211 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// Frames from these packages belong to the game, the JVM or the loaders, never to a mod.
+const IGNORED_PACKAGES: [&str; 20] = [
+    "java.",
+    "javax.",
+    "jdk.",
+    "sun.",
+    "com.sun.",
+    "net.minecraft.",
+    "com.mojang.",
+    "org.lwjgl.",
+    "net.fabricmc.",
+    "org.quiltmc.",
+    "cpw.mods.",
+    "net.minecraftforge.",
+    "org.spongepowered.",
+    "com.google.",
+    "org.apache.",
+    "io.netty.",
+    "it.unimi.",
+    "oshi.",
+    "org.objectweb.",
+    "com.mumfrey.liteloader.",
+];
+
+// Only the first mods found are shown, the rest is usually the same stack.
+const MAX_SUSPECTS: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CrashKind {
+    Minecraft,
+    Jvm,
+}
+
+#[derive(Clone)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    pub kind: CrashKind,
+    pub description: Option<String>,
+    pub exception: Option<String>,
+    pub suspected_mods: Vec<String>,
+    pub content: String,
+}
+
+/// Crash reports and JVM error logs written in the game directory after `since`, newest first.
+pub fn find_new_reports(game_dir: &Path, since: SystemTime) -> Vec<CrashReport> {
+    let mut paths = vec![];
+
+    paths.extend(list_files(&game_dir.join("crash-reports"), |name| {
+        name.starts_with("crash-") && name.ends_with(".txt")
+    }));
+    paths.extend(list_files(game_dir, |name| {
+        name.starts_with("hs_err_pid") && name.ends_with(".log")
+    }));
+
+    let mut reports: Vec<(SystemTime, CrashReport)> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            if modified < since {
+                return None;
+            }
+            read_report(&path).map(|report| (modified, report))
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.0.cmp(&a.0));
+    reports.into_iter().map(|(_, report)| report).collect()
+}
+
+pub fn read_report(path: &Path) -> Option<CrashReport> {
+    let content = match fs::read(path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+        Err(_) => return None,
+    };
+
+    let is_jvm = path.file_name().map_or(false, |name| {
+        name.to_string_lossy().starts_with("hs_err_pid")
+    });
+
+    Some(if is_jvm {
+        CrashReport {
+            path: path.to_path_buf(),
+            kind: CrashKind::Jvm,
+            description: get_jvm_problematic_frame(&content),
+            exception: get_jvm_error(&content),
+            suspected_mods: vec![],
+            content,
+        }
+    } else {
+        CrashReport {
+            path: path.to_path_buf(),
+            kind: CrashKind::Minecraft,
+            description: content
+                .lines()
+                .find_map(|line| line.strip_prefix("Description: "))
+                .map(|description| description.trim().to_string()),
+            exception: get_exception(&content),
+            suspected_mods: get_suspected_mods(&content),
+            content,
+        }
+    })
+}
+
+fn list_files(folder: &Path, filter: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    match fs::read_dir(folder) {
+        Ok(read_dir) => read_dir
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| filter(&entry.file_name().to_string_lossy()))
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+// The exception is the first line after the description, right before its stack trace.
+fn get_exception(content: &str) -> Option<String> {
+    content
+        .lines()
+        .skip_while(|line| !line.starts_with("Description: "))
+        .skip(1)
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line.trim().to_string())
+}
+
+fn get_suspected_mods(content: &str) -> Vec<String> {
+    let mut suspects: Vec<String> = vec![];
+
+    // Recent Forge and Fabric versions already name the culprits.
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.starts_with("Suspected Mod") {
+            if let Some((_, inline)) = line.split_once(':') {
+                push_suspect(&mut suspects, inline);
+            }
+            for suspect in lines.by_ref() {
+                if suspect.trim().is_empty() || !suspect.starts_with(char::is_whitespace) {
+                    break;
+                }
+                push_suspect(&mut suspects, suspect);
+            }
+        }
+    }
+    if !suspects.is_empty() {
+        return suspects;
+    }
+
+    for line in content.lines() {
+        let frame = match line.trim().strip_prefix("at ") {
+            Some(frame) => frame,
+            None => continue,
+        };
+
+        // Mixins applied to a frame are listed as "pl:mixin:APP:modid.mixins.json:..." by Forge.
+        for part in frame.split(|c| c == ',' || c == '{' || c == '}') {
+            if let Some(config) = part.split(':').find(|part| part.ends_with(".mixins.json")) {
+                push_suspect(&mut suspects, config.trim_end_matches(".mixins.json"));
+            }
+        }
+
+        let method = frame.split('(').next().unwrap_or(frame);
+        if !IGNORED_PACKAGES
+            .iter()
+            .any(|package| method.starts_with(package))
+        {
+            // Up to three package segments are usually enough to recognize the mod.
+            let package: Vec<&str> = method.split('.').take(3).collect();
+            push_suspect(&mut suspects, &package.join("."));
+        }
+
+        if suspects.len() >= MAX_SUSPECTS {
+            break;
+        }
+    }
+
+    suspects
+}
+
+fn push_suspect(suspects: &mut Vec<String>, suspect: &str) {
+    let suspect = suspect.trim();
+    if !suspect.is_empty()
+        && !suspect.eq_ignore_ascii_case("none")
+        && suspects.len() < MAX_SUSPECTS
+        && !suspects.iter().any(|known| known == suspect)
+    {
+        suspects.push(suspect.to_string());
+    }
+}
+
+// "#  SIGSEGV (0xb) at pc=..." or "#  EXCEPTION_ACCESS_VIOLATION (0xc0000005) at pc=...".
+fn get_jvm_error(content: &str) -> Option<String> {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("#  "))
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn get_jvm_problematic_frame(content: &str) -> Option<String> {
+    content
+        .lines()
+        .skip_while(|line| !line.starts_with("# Problematic frame:"))
+        .nth(1)
+        .and_then(|line| line.strip_prefix("#"))
+        .map(|line| line.trim().to_string())
+}
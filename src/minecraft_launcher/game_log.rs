@@ -0,0 +1,360 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde_derive::Deserialize;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{ChildStderr, ChildStdout};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+// Enough for a long session without letting the TUI grow forever.
+pub const DEFAULT_CAPACITY: usize = 5000;
+
+const EVENT_START: &str = "<log4j:Event";
+const EVENT_END: &str = "</log4j:Event>";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LogSource {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub fn from_str(level: &str) -> Option<LogLevel> {
+        match level.trim().to_uppercase().as_str() {
+            "TRACE" | "FINEST" | "FINER" => Some(LogLevel::Trace),
+            "DEBUG" | "FINE" => Some(LogLevel::Debug),
+            "INFO" => Some(LogLevel::Info),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "ERROR" => Some(LogLevel::Error),
+            "FATAL" | "SEVERE" => Some(LogLevel::Fatal),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LogLine {
+    pub time: DateTime<Local>,
+    pub source: LogSource,
+    pub level: Option<LogLevel>,
+    pub logger: Option<String>,
+    pub thread: Option<String>,
+    pub text: String,
+    pub throwable: Option<String>,
+}
+
+impl LogLine {
+    fn from_text(text: String, source: LogSource) -> LogLine {
+        let (thread, level) = parse_plain_header(&text);
+
+        LogLine {
+            time: Local::now(),
+            source,
+            level,
+            logger: None,
+            thread,
+            text,
+            throwable: None,
+        }
+    }
+
+    /// Lines without a known level count as errors on stderr and as infos on stdout.
+    pub fn get_level(&self) -> LogLevel {
+        match (self.level, self.source) {
+            (Some(level), _) => level,
+            (None, LogSource::Stderr) => LogLevel::Error,
+            (None, LogSource::Stdout) => LogLevel::Info,
+        }
+    }
+}
+
+// Attributes and elements of the log4j XMLLayout, the namespace prefix is dropped by the parser.
+#[derive(Deserialize)]
+struct Log4jEvent {
+    logger: Option<String>,
+    timestamp: Option<String>,
+    level: Option<String>,
+    thread: Option<String>,
+    #[serde(rename = "Message")]
+    message: Option<String>,
+    #[serde(rename = "Throwable")]
+    throwable: Option<String>,
+}
+
+/// Lines of a running game, read from both pipes in the order they arrive.
+pub struct GameLog {
+    pub lines: VecDeque<LogLine>,
+    // Lines received since the game started, dropped ones included.
+    pub received: usize,
+    capacity: usize,
+    receiver: Option<Receiver<LogLine>>,
+}
+
+impl GameLog {
+    pub fn new(capacity: usize) -> GameLog {
+        GameLog {
+            lines: VecDeque::new(),
+            received: 0,
+            capacity,
+            receiver: None,
+        }
+    }
+
+    /// Starts one reader thread per pipe, the previous lines are dropped.
+    pub fn attach(&mut self, stdout: Option<ChildStdout>, stderr: Option<ChildStderr>) {
+        let (tx, rx) = channel();
+
+        if let Some(stdout) = stdout {
+            spawn_reader(stdout, LogSource::Stdout, tx.clone());
+        }
+        if let Some(stderr) = stderr {
+            spawn_reader(stderr, LogSource::Stderr, tx);
+        }
+
+        self.lines.clear();
+        self.received = 0;
+        self.receiver = Some(rx);
+    }
+
+    /// Returns the line dropped to make room, if any.
+    pub fn push(&mut self, line: LogLine) -> Option<LogLine> {
+        let dropped = if self.lines.len() >= self.capacity {
+            self.lines.pop_front()
+        } else {
+            None
+        };
+        self.lines.push_back(line);
+
+        dropped
+    }
+
+    /// Moves the lines read since the last call into the buffer, returns the dropped ones.
+    pub fn poll(&mut self) -> Vec<LogLine> {
+        let lines: Vec<LogLine> = match &self.receiver {
+            None => return vec![],
+            Some(receiver) => receiver.try_iter().collect(),
+        };
+
+        self.received += lines.len();
+        lines
+            .into_iter()
+            .filter_map(|line| self.push(line))
+            .collect()
+    }
+}
+
+fn spawn_reader<R: Read + Send + 'static>(pipe: R, source: LogSource, tx: Sender<LogLine>) {
+    thread::spawn(move || {
+        // An XML event spans several lines, they are kept here until it is closed.
+        let mut event: Option<String> = None;
+
+        for line in BufReader::new(pipe).split(b'\n') {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            // Games don't always log in UTF-8, a lossy line beats a dead reader.
+            let text = String::from_utf8_lossy(&line)
+                .trim_end_matches('\r')
+                .to_string();
+
+            let log_line = match event.take() {
+                None if text.trim_start().starts_with(EVENT_START) => {
+                    if text.contains(EVENT_END) {
+                        parse_event(&text, source)
+                    } else {
+                        event = Some(text);
+                        continue;
+                    }
+                }
+                None => LogLine::from_text(text, source),
+                Some(mut xml) => {
+                    xml.push('\n');
+                    xml.push_str(&text);
+                    if text.contains(EVENT_END) {
+                        parse_event(&xml, source)
+                    } else {
+                        event = Some(xml);
+                        continue;
+                    }
+                }
+            };
+
+            if tx.send(log_line).is_err() {
+                return;
+            }
+        }
+
+        // The game died in the middle of an event, better show it raw than lose it.
+        if let Some(xml) = event {
+            tx.send(LogLine::from_text(xml, source)).unwrap_or(());
+        }
+    });
+}
+
+fn parse_event(xml: &str, source: LogSource) -> LogLine {
+    // The prefix is never declared by log4j, the parser refuses unbound prefixes.
+    let declared = xml.replacen(
+        EVENT_START,
+        "<log4j:Event xmlns:log4j=\"http://jakarta.apache.org/log4j/\"",
+        1,
+    );
+
+    match serde_xml_rs::from_str::<Log4jEvent>(&declared) {
+        Err(_) => LogLine::from_text(xml.to_string(), source),
+        Ok(event) => LogLine {
+            time: event
+                .timestamp
+                .and_then(|timestamp| timestamp.parse::<i64>().ok())
+                .and_then(|timestamp| Local.timestamp_millis_opt(timestamp).single())
+                .unwrap_or_else(Local::now),
+            source,
+            level: event.level.as_deref().and_then(LogLevel::from_str),
+            logger: event.logger,
+            thread: event.thread,
+            text: event.message.unwrap_or_default(),
+            throwable: event.throwable,
+        },
+    }
+}
+
+// Reads "[12:34:56] [Render thread/INFO]: ..." and "2013-01-01 12:34:56 [INFO] ..." prefixes.
+fn parse_plain_header(text: &str) -> (Option<String>, Option<LogLevel>) {
+    if !text.starts_with(|c: char| c == '[' || c.is_ascii_digit()) {
+        return (None, None);
+    }
+
+    let header = match text.find("]:").or_else(|| text.find(']')) {
+        Some(end) => &text[..end],
+        None => return (None, None),
+    };
+    let last = match header.rfind('[') {
+        Some(start) => &header[start + 1..],
+        None => return (None, None),
+    };
+
+    match last.rfind('/') {
+        Some(separator) => match LogLevel::from_str(&last[separator + 1..]) {
+            Some(level) => (Some(last[..separator].to_string()), Some(level)),
+            None => (None, None),
+        },
+        None => (
+            None,
+            LogLevel::from_str(last.rsplit(' ').next().unwrap_or(last)),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const EVENT: &str = "<log4j:Event logger=\"net.minecraft.client.Minecraft\" timestamp=\"1356995696000\" level=\"ERROR\" thread=\"Render thread\">
+\t<log4j:Message><![CDATA[Unreported exception thrown!]]></log4j:Message>
+\t<log4j:Throwable><![CDATA[java.lang.IllegalStateException: Boom
+\tat net.minecraft.client.Minecraft.run(Minecraft.java:42)
+]]></log4j:Throwable>
+</log4j:Event>";
+
+    fn read_lines(output: &str) -> Vec<LogLine> {
+        let (tx, rx) = channel();
+        spawn_reader(
+            Cursor::new(output.as_bytes().to_vec()),
+            LogSource::Stdout,
+            tx,
+        );
+        rx.iter().collect()
+    }
+
+    #[test]
+    fn multi_line_event() {
+        let lines = read_lines(&format!("{}\n", EVENT));
+
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert_eq!(line.text, "Unreported exception thrown!");
+        assert!(line.level == Some(LogLevel::Error));
+        assert_eq!(
+            line.logger.as_deref(),
+            Some("net.minecraft.client.Minecraft")
+        );
+        assert_eq!(line.thread.as_deref(), Some("Render thread"));
+        assert_eq!(line.time.timestamp_millis(), 1356995696000);
+        assert_eq!(
+            line.throwable.as_deref(),
+            Some(
+                "java.lang.IllegalStateException: Boom\n\tat net.minecraft.client.Minecraft.run(Minecraft.java:42)"
+            )
+        );
+    }
+
+    #[test]
+    fn plain_headers() {
+        let cases = [
+            (
+                "2013-01-01 12:34:56 [INFO] Setting user: Player",
+                None,
+                Some(LogLevel::Info),
+            ),
+            (
+                "[12:34:56] [Render thread/WARN]: Missing sound for event",
+                Some("Render thread"),
+                Some(LogLevel::Warn),
+            ),
+            (
+                "[12:34:56] [main/INFO]: Loading",
+                Some("main"),
+                Some(LogLevel::Info),
+            ),
+            ("Exception in thread \"main\"", None, None),
+        ];
+
+        for (i, (text, thread, level)) in cases.iter().enumerate() {
+            let (parsed_thread, parsed_level) = parse_plain_header(text);
+            assert_eq!(parsed_thread.as_deref(), *thread, "case {}", i);
+            assert!(parsed_level == *level, "case {}", i);
+        }
+    }
+
+    #[test]
+    fn events_and_plain_lines_keep_their_order() {
+        let lines = read_lines(&format!(
+            "2013-01-01 12:34:56 [INFO] Before\n{}\n[12:34:56] [Render thread/WARN]: After\n",
+            EVENT
+        ));
+
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "2013-01-01 12:34:56 [INFO] Before",
+                "Unreported exception thrown!",
+                "[12:34:56] [Render thread/WARN]: After"
+            ]
+        );
+        assert!(lines[2].get_level() == LogLevel::Warn);
+    }
+}
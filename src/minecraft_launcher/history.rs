@@ -0,0 +1,217 @@
+use crate::minecraft_launcher::path;
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// What the user asked to play, the installed version id alone doesn't tell the loader.
+#[derive(Clone)]
+pub struct LaunchTarget {
+    pub minecraft_version: String,
+    pub loader: String,
+    pub loader_version: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SessionRecord {
+    pub id: String,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    #[serde(rename = "minecraftVersion")]
+    pub minecraft_version: String,
+    pub loader: String,
+    #[serde(rename = "loaderVersion", skip_serializing_if = "Option::is_none")]
+    pub loader_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    pub account: String,
+    pub started: String,
+    // Missing when the launcher was closed before the game.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended: Option<String>,
+    #[serde(rename = "exitCode", skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+impl SessionRecord {
+    pub fn new(
+        version_id: String,
+        target: LaunchTarget,
+        instance: Option<String>,
+        profile_id: String,
+        account: String,
+    ) -> SessionRecord {
+        SessionRecord {
+            id: Uuid::new_v4().to_simple().to_string(),
+            version_id,
+            minecraft_version: target.minecraft_version,
+            loader: target.loader,
+            loader_version: target.loader_version,
+            instance,
+            profile_id,
+            account,
+            started: get_timestamp(),
+            ended: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn get_started(&self) -> Option<DateTime<Local>> {
+        parse_timestamp(&self.started)
+    }
+
+    pub fn get_playtime(&self) -> Duration {
+        match (
+            self.get_started(),
+            self.ended.as_deref().and_then(parse_timestamp),
+        ) {
+            (Some(started), Some(ended)) => (ended - started).to_std().unwrap_or_default(),
+            _ => Duration::default(),
+        }
+    }
+
+    fn is_same_target(&self, other: &SessionRecord) -> bool {
+        self.minecraft_version == other.minecraft_version
+            && self.loader == other.loader
+            && self.loader_version == other.loader_version
+            && self.profile_id == other.profile_id
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Playtime {
+    pub total: Duration,
+    pub last_played: DateTime<Local>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct History {
+    #[serde(default)]
+    sessions: Vec<SessionRecord>,
+}
+
+pub fn get_history_path() -> PathBuf {
+    match path::get_launcher_folder() {
+        None => path::get_minecraft_directory().join("rusty-craft-history.json"),
+        Some(launcher_folder) => launcher_folder.join("history.json"),
+    }
+}
+
+/// Every recorded session, oldest first.
+pub fn read_history() -> Result<Vec<SessionRecord>, String> {
+    let history_path = get_history_path();
+
+    if !history_path.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut file = match File::open(&history_path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Unable to open history.json: {}", err)),
+    };
+
+    let mut body = String::new();
+    match file.read_to_string(&mut body) {
+        Ok(_) => match serde_json::from_str::<History>(&body) {
+            Ok(history) => Ok(history.sessions),
+            Err(err) => Err(format!("history.json wrongly formatted! {}", err)),
+        },
+        Err(err) => Err(format!("Unable to read history.json: {}", err)),
+    }
+}
+
+fn write_history(sessions: Vec<SessionRecord>) -> Result<(), String> {
+    match serde_json::to_string_pretty(&History { sessions }) {
+        Ok(body) => match File::create(get_history_path()) {
+            Ok(mut file) => match file.write_all(body.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(format!("Unable to write history.json: {}", err)),
+            },
+            Err(err) => Err(format!("Unable to create history.json: {}", err)),
+        },
+        Err(err) => Err(format!("Unable to serialize history: {}", err)),
+    }
+}
+
+pub fn add_record(record: &SessionRecord) -> Result<(), String> {
+    // Never overwrite a history we couldn't understand.
+    let mut sessions = read_history()?;
+    sessions.push(record.clone());
+    write_history(sessions)
+}
+
+pub fn finish_record(record_id: &str, exit_code: Option<i32>) -> Result<(), String> {
+    let mut sessions = read_history()?;
+
+    match sessions.iter_mut().find(|record| record.id == record_id) {
+        None => Ok(()),
+        Some(record) => {
+            record.ended = Some(get_timestamp());
+            record.exit_code = exit_code;
+            write_history(sessions)
+        }
+    }
+}
+
+/// Total and last played time of each Minecraft version, loaders included.
+pub fn get_playtimes(sessions: &[SessionRecord]) -> HashMap<String, Playtime> {
+    let mut playtimes: HashMap<String, Playtime> = HashMap::new();
+
+    for record in sessions {
+        let started = match record.get_started() {
+            Some(started) => started,
+            None => continue,
+        };
+
+        let playtime = playtimes
+            .entry(record.minecraft_version.clone())
+            .or_insert(Playtime {
+                total: Duration::default(),
+                last_played: started,
+            });
+        playtime.total += record.get_playtime();
+        if started > playtime.last_played {
+            playtime.last_played = started;
+        }
+    }
+
+    playtimes
+}
+
+/// The last distinct things played, newest first.
+pub fn get_recent(sessions: &[SessionRecord], count: usize) -> Vec<SessionRecord> {
+    let mut recent: Vec<SessionRecord> = vec![];
+
+    for record in sessions.iter().rev() {
+        if recent.len() >= count {
+            break;
+        }
+        if !recent.iter().any(|known| known.is_same_target(record)) {
+            recent.push(record.clone());
+        }
+    }
+
+    recent
+}
+
+pub fn format_playtime(playtime: Duration) -> String {
+    let minutes = playtime.as_secs() / 60;
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+fn get_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|time| time.with_timezone(&Local))
+}
@@ -3,15 +3,18 @@ use crate::minecraft_launcher::{
     // manifest::assets,
     manifest::version,
     manifest::version::{LibraryDownloadArtifact, RuleAction},
-    path,
+    path, profiles, utils,
 };
 
 use crate::minecraft_launcher::app::download_tab::Message;
+use crate::minecraft_launcher::arguments::Platform;
+use crate::minecraft_launcher::library_overrides::LibraryOverrides;
 use crate::minecraft_launcher::manifest;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 use crate::minecraft_launcher::modding::ModLoaderInstaller;
 use crate::minecraft_launcher::manifest::version::Main;
@@ -24,7 +27,8 @@ pub fn install_version(
     id: String,
     versions: Vec<manifest::main::Version>,
     tx: Sender<Message>,
-    modded_version: Option<Main>
+    modded_version: Option<Main>,
+    overrides: &LibraryOverrides,
 ) -> Option<()> {
     tx.send(Message::NewSubStep(
         String::from("Checking Version folder"),
@@ -38,11 +42,17 @@ pub fn install_version(
         None => if let Some(version_folder) = path::get_version_folder(&id) {
             let manifest_file_path = version_folder.join(id.clone() + ".json");
             if manifest_file_path.exists() {
-                read_version_manifest_and_install(manifest_file_path, tx)
+                read_version_manifest_and_install(manifest_file_path, versions, tx, overrides)
             } else {
-                for version in versions {
+                for version in versions.clone() {
                     if version.id == id {
-                        return download_and_install_vanilla(version, manifest_file_path, tx);
+                        return download_and_install_vanilla(
+                            version,
+                            manifest_file_path,
+                            versions,
+                            tx,
+                            overrides,
+                        );
                     }
                 }
                 None
@@ -63,7 +73,7 @@ pub fn install_version(
                         Some(vanilla_version) => {
                             let version = Main::inherit(modded_version, &vanilla_version);
                             write_version_manifest(&version);
-                            install_version_from_manifest(&version, tx)
+                            install_version_from_manifest(&version, tx, overrides)
                         }
                     }
                 } else {
@@ -111,7 +121,9 @@ fn write_version_manifest(version_manifest: &Main) {
 fn download_and_install_vanilla(
     version: manifest::main::Version,
     file_path: PathBuf,
+    versions: Vec<manifest::main::Version>,
     tx: Sender<Message>,
+    overrides: &LibraryOverrides,
 ) -> Option<()> {
     tx.send(Message::NewSubStep(
         String::from("Downloading Version manifest"),
@@ -121,7 +133,7 @@ fn download_and_install_vanilla(
         .unwrap_or(());
 
     match path::download_file_to(&version.url, &file_path) {
-        Ok(_) => read_version_manifest_and_install(file_path, tx),
+        Ok(_) => read_version_manifest_and_install(file_path, versions, tx, overrides),
         Err(_) => None,
     }
 }
@@ -140,7 +152,12 @@ fn read_version_manifest(manifest_path: PathBuf, tx: Sender<Message>) -> Option<
     None
 }
 
-fn read_version_manifest_and_install(manifest_path: PathBuf, tx: Sender<Message>) -> Option<()> {
+fn read_version_manifest_and_install(
+    manifest_path: PathBuf,
+    versions: Vec<manifest::main::Version>,
+    tx: Sender<Message>,
+    overrides: &LibraryOverrides,
+) -> Option<()> {
     tx.send(Message::NewSubStep(
         String::from("Reading Version manifest"),
         3,
@@ -150,20 +167,98 @@ fn read_version_manifest_and_install(manifest_path: PathBuf, tx: Sender<Message>
 
     match read_version_manifest(manifest_path, tx.clone()) {
         None => None,
-        Some(version) => install_version_from_manifest(&version, tx)
+        Some(version) => match resolve_inheritance(version, versions, tx.clone()) {
+            None => None,
+            Some(version) => install_version_from_manifest(&version, tx, overrides),
+        },
+    }
+}
+
+// Versions added by external installers (Forge, OptiFine...) only contain what differs
+// from the version they inherit from.
+fn resolve_inheritance(
+    version: Main,
+    versions: Vec<manifest::main::Version>,
+    tx: Sender<Message>,
+) -> Option<Main> {
+    match version.inherits_from.clone() {
+        None => Some(version),
+        Some(inherit_from) => match path::get_version_folder(&inherit_from) {
+            None => {
+                tx.send(Message::Error(format!(
+                    "Unable to access or create version folder of {}",
+                    inherit_from
+                )))
+                .unwrap_or(());
+                None
+            }
+            Some(version_folder) => {
+                let manifest_file_path = version_folder.join(inherit_from.clone() + ".json");
+
+                if !manifest_file_path.exists() {
+                    match versions.iter().find(|v| v.id == inherit_from) {
+                        None => {
+                            tx.send(Message::Error(format!(
+                                "Unable to find version {} inherited by {}",
+                                inherit_from, version.id
+                            )))
+                            .unwrap_or(());
+                            return None;
+                        }
+                        Some(parent) => {
+                            if let Err(err) =
+                                path::download_file_to(&parent.url, &manifest_file_path)
+                            {
+                                tx.send(Message::Error(err)).unwrap_or(());
+                                return None;
+                            }
+                        }
+                    }
+                }
+
+                match read_version_manifest(manifest_file_path, tx.clone()) {
+                    None => {
+                        tx.send(Message::Error(format!(
+                            "Unable to read manifest of version {}",
+                            inherit_from
+                        )))
+                        .unwrap_or(());
+                        None
+                    }
+                    Some(parent) => match resolve_inheritance(parent, versions, tx) {
+                        None => None,
+                        Some(parent) => Some(Main::inherit(version, &parent)),
+                    },
+                }
+            }
+        },
     }
 }
 
 fn install_version_from_manifest(
     version_manifest: &version::Main,
     tx: Sender<Message>,
+    overrides: &LibraryOverrides,
 ) -> Option<()> {
+    // The launch uses the manifest sent with Done, so the replaced libraries end up on the
+    // classpath too.
+    let version_manifest = &match overrides.apply(version_manifest) {
+        Ok(version_manifest) => version_manifest,
+        Err(err) => {
+            tx.send(Message::Error(err)).unwrap_or(());
+            return None;
+        }
+    };
+
     if let Some(tx) = java::check_java_version(version_manifest, tx) {
         if let Some(tx) = install_client_jar(version_manifest, tx) {
             if let Some(tx) = install_libraries(version_manifest, tx) {
                 if let Some(tx) = assets::install_assets_index(version_manifest, tx) {
                     if let Some(tx) = check_log_file(version_manifest, tx) {
                         launch::pre_launch(version_manifest.clone(), tx.clone());
+                        if let Err(err) = profiles::add_version_profile(&version_manifest.id) {
+                            tx.send(Message::Error(err)).unwrap_or(());
+                        }
                         tx.send(Message::Done(version_manifest.clone()))
                             .unwrap_or(());
                         return Some(());
@@ -255,6 +350,7 @@ fn install_libraries(
     let library_count = version_manifest.libraries.len();
 
     let mut index = 0;
+    let platform = Platform::current();
 
     for library in version_manifest.libraries {
         index += 1;
@@ -272,7 +368,7 @@ fn install_libraries(
 
         let allowed = match library.rules {
             None => RuleAction::Allow,
-            Some(rules) => arguments::match_rules(rules, None),
+            Some(rules) => arguments::match_rules(&rules, &HashSet::new(), &platform),
         };
 
         match allowed {
@@ -296,10 +392,10 @@ fn install_libraries(
                                         if lib_path.exists() && lib_path.is_file() {
                                             match lib_path.metadata() {
                                                 Ok(meta) => {
-                                                    if meta.len() != artifact.size {
-                                                        match path::download_file_to(
-                                                            &artifact.url,
-                                                            &lib_path,
+                                                    if !is_intact(&artifact, &lib_path, meta.len())
+                                                    {
+                                                        match download_artifact(
+                                                            &artifact, &lib_path,
                                                         ) {
                                                             Ok(_) => {}
                                                             Err(err) => {
@@ -322,7 +418,7 @@ fn install_libraries(
                                                 }
                                             }
                                         } else {
-                                            match path::download_file_to(&artifact.url, &lib_path) {
+                                            match download_artifact(&artifact, &lib_path) {
                                                 Ok(_) => {}
                                                 Err(err) => {
                                                     tx.send(Message::Error(err)).unwrap_or(());
@@ -366,10 +462,8 @@ fn install_libraries(
                                     if lib_path.exists() && lib_path.is_file() {
                                         match lib_path.metadata() {
                                             Ok(meta) => {
-                                                if meta.len() != class.size {
-                                                    match path::download_file_to(
-                                                        &class.url, &lib_path,
-                                                    ) {
+                                                if !is_intact(class, &lib_path, meta.len()) {
+                                                    match download_artifact(class, &lib_path) {
                                                         Ok(_) => {}
                                                         Err(err) => {
                                                             tx.send(Message::Error(err))
@@ -388,7 +482,7 @@ fn install_libraries(
                                             }
                                         }
                                     } else {
-                                        match path::download_file_to(&class.url, &lib_path) {
+                                        match download_artifact(class, &lib_path) {
                                             Ok(_) => {}
                                             Err(err) => {
                                                 tx.send(Message::Error(err)).unwrap_or(());
@@ -478,6 +572,35 @@ fn install_libraries(
     }
 }
 
+// Replaced libraries have no known size, only the SHA-1 published by their repository.
+fn is_intact(artifact: &LibraryDownloadArtifact, lib_path: &Path, len: u64) -> bool {
+    if artifact.size != 0 {
+        return len == artifact.size;
+    }
+
+    artifact.sha1.is_empty()
+        || utils::get_file_sha1(lib_path).map_or(false, |sha1| sha1 == artifact.sha1)
+}
+
+fn download_artifact(artifact: &LibraryDownloadArtifact, lib_path: &Path) -> Result<(), String> {
+    // download_file_to can't write over an existing file, the damaged one goes first.
+    if lib_path.is_file() {
+        fs::remove_file(lib_path).unwrap_or(());
+    }
+    path::download_file_to(&artifact.url, lib_path)?;
+
+    let len = lib_path.metadata().map_or(0, |meta| meta.len());
+    if is_intact(artifact, lib_path, len) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} doesn't match the checksum of {}",
+            lib_path.display(),
+            artifact.url
+        ))
+    }
+}
+
 fn check_log_file(
     version_manifest: &version::Main,
     tx: Sender<Message>,
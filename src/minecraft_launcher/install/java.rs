@@ -11,11 +11,15 @@ use crate::minecraft_launcher::manifest;
 use crate::minecraft_launcher::manifest::java_versions::Version;
 use crate::minecraft_launcher::manifest::{java_versions, version};
 use crate::minecraft_launcher::path;
+use crate::minecraft_launcher::{config, jvm};
+use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 
+mod adoptium;
+
 pub fn check_java_version(
     version_manifest: &version::Main,
     tx: Sender<Message>,
@@ -29,6 +33,14 @@ pub fn check_java_version(
         5,
     ))
     .expect("Can't send message to renderer thread");
+
+    if config::read_config().unwrap_or_default().prefer_system_java {
+        let major_version = version_manifest.get_java_major_version();
+        if let Ok(system_jvm) = jvm::find_jvm_for_version(major_version) {
+            return use_system_jvm(system_jvm, tx);
+        }
+    }
+
     match get_java_version_manifest() {
         Err(_err) => {
             tx.send(Message::NewSubStep(
@@ -62,20 +74,24 @@ pub fn check_java_version(
                                         .unwrap_or(());
                                     Some(tx)
                                 } else {
-                                    tx.send(Message::Error(format!(
-                                        "Unable to find java executable: {}",
-                                        bin.join(get_java_ex_for_os()).display()
-                                    )))
-                                    .unwrap_or(());
-                                    None
+                                    fallback_to_system_jvm(
+                                        &version_manifest,
+                                        format!(
+                                            "Unable to find java executable: {}",
+                                            bin.join(get_java_ex_for_os()).display()
+                                        ),
+                                        tx,
+                                    )
                                 }
                             } else {
-                                tx.send(Message::Error(format!(
-                                    "Unable to find java folder: {}",
-                                    java_folder.display()
-                                )))
-                                .unwrap_or(());
-                                None
+                                fallback_to_system_jvm(
+                                    &version_manifest,
+                                    format!(
+                                        "Unable to find java folder: {}",
+                                        java_folder.display()
+                                    ),
+                                    tx,
+                                )
                             }
                         }
                     }
@@ -83,89 +99,302 @@ pub fn check_java_version(
             }
         }
 
-        Ok(manifest) => match manifest.get_os_version() {
-            None => {
-                tx.send(Message::Error(String::from("Unable to get os_version")))
+        Ok(manifest) => {
+            tx.send(Message::NewSubStep(
+                String::from("Getting right java version"),
+                2,
+                5,
+            ))
+            .unwrap_or(());
+            let java_v_type = match version_manifest.java_version.clone() {
+                None => String::from("jre-legacy"),
+                Some(ver) => ver.component,
+            };
+            let major_version = version_manifest.get_java_major_version();
+
+            for provider in get_runtime_providers(manifest) {
+                match provider.get_runtime_version(&java_v_type, major_version) {
+                    Ok(None) => {}
+                    Ok(Some(online_version)) => {
+                        return check_runtime(
+                            provider.as_ref(),
+                            java_v_type,
+                            major_version,
+                            online_version,
+                            tx,
+                        );
+                    }
+                    Err(err) => {
+                        tx.send(Message::Error(format!("{}: {}", provider.get_name(), err)))
+                            .unwrap_or(());
+                    }
+                }
+            }
+
+            fallback_to_system_jvm(
+                &version_manifest,
+                format!(
+                    "No runtime provider has '{}' for {}",
+                    java_v_type,
+                    path::get_os_java_name()
+                ),
+                tx,
+            )
+        }
+    }
+}
+
+/// Installs `component` into `os_folder` from the first provider having it, whatever is already
+/// there.
+pub fn install_runtime(
+    component: &str,
+    major_version: u8,
+    os_folder: PathBuf,
+    tx: Sender<Message>,
+) -> Option<Sender<Message>> {
+    let manifest = match get_java_version_manifest() {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            tx.send(Message::Error(err)).unwrap_or(());
+            return None;
+        }
+    };
+
+    for provider in get_runtime_providers(manifest) {
+        match provider.get_runtime_version(component, major_version) {
+            Ok(None) => {}
+            Ok(Some(online_version)) => {
+                return install(
+                    provider.as_ref(),
+                    component,
+                    major_version,
+                    os_folder,
+                    online_version,
+                    tx,
+                );
+            }
+            Err(err) => {
+                tx.send(Message::Error(format!("{}: {}", provider.get_name(), err)))
                     .unwrap_or(());
+            }
+        }
+    }
+
+    tx.send(Message::Error(format!(
+        "No runtime provider has '{}' for {}",
+        component,
+        path::get_os_java_name()
+    )))
+    .unwrap_or(());
+    None
+}
+
+/// Puts `new_folder` in place of `folder`, the previous content is only deleted once the new one
+/// is there.
+pub(crate) fn replace_folder(new_folder: &Path, folder: &Path) -> Result<(), String> {
+    let mut old_name = folder.file_name().unwrap_or_default().to_os_string();
+    old_name.push(".old");
+    let old_folder = folder.with_file_name(old_name);
+
+    if old_folder.exists() {
+        if let Err(err) = fs::remove_dir_all(&old_folder) {
+            return Err(format!(
+                "Unable to remove {}: {}",
+                old_folder.display(),
+                err
+            ));
+        }
+    }
+
+    if folder.exists() {
+        if let Err(err) = fs::rename(folder, &old_folder) {
+            return Err(format!("Unable to move {}: {}", folder.display(), err));
+        }
+    }
+
+    if let Err(err) = fs::rename(new_folder, folder) {
+        if old_folder.exists() {
+            fs::rename(&old_folder, folder).unwrap_or(());
+        }
+        return Err(format!("Unable to move {}: {}", new_folder.display(), err));
+    }
+
+    if old_folder.exists() {
+        fs::remove_dir_all(&old_folder).unwrap_or(());
+    }
+
+    Ok(())
+}
+
+// Mojang doesn't ship every runtime for every platform (aarch64 Linux, musl...).
+fn get_runtime_providers(manifest: java_versions::Main) -> Vec<Box<dyn RuntimeProvider>> {
+    vec![
+        Box::new(MojangRuntimeProvider { manifest }),
+        Box::new(adoptium::AdoptiumRuntimeProvider::new()),
+    ]
+}
+
+pub trait RuntimeProvider {
+    fn get_name(&self) -> String;
+
+    /// Name of the runtime version the provider would install, `None` if it doesn't have one
+    /// for this platform.
+    fn get_runtime_version(
+        &self,
+        component: &str,
+        major_version: u8,
+    ) -> Result<Option<String>, String>;
+
+    fn install_runtime(
+        &self,
+        component: &str,
+        major_version: u8,
+        os_folder: PathBuf,
+        online_version: String,
+        tx: Sender<Message>,
+    ) -> Option<Sender<Message>>;
+}
+
+pub struct MojangRuntimeProvider {
+    manifest: java_versions::Main,
+}
+
+impl MojangRuntimeProvider {
+    fn get_runtime(&self, component: &str) -> Option<Version> {
+        match self.manifest.clone().get_os_version() {
+            None => None,
+            Some(os_version) => match os_version.get_java_version(component) {
+                None => None,
+                Some(versions) => versions.get(0).cloned(),
+            },
+        }
+    }
+}
+
+impl RuntimeProvider for MojangRuntimeProvider {
+    fn get_name(&self) -> String {
+        "Mojang".to_string()
+    }
+
+    fn get_runtime_version(
+        &self,
+        component: &str,
+        _major_version: u8,
+    ) -> Result<Option<String>, String> {
+        // Mojang's Linux runtimes are linked against glibc.
+        if cfg!(target_env = "musl") {
+            return Ok(None);
+        }
+
+        Ok(self
+            .get_runtime(component)
+            .map(|version| version.version.name))
+    }
+
+    fn install_runtime(
+        &self,
+        component: &str,
+        _major_version: u8,
+        os_folder: PathBuf,
+        online_version: String,
+        tx: Sender<Message>,
+    ) -> Option<Sender<Message>> {
+        match self.get_runtime(component) {
+            None => {
+                tx.send(Message::Error(format!(
+                    "Unable to get java_version from type '{}'",
+                    component
+                )))
+                .unwrap_or(());
                 None
             }
-            Some(os_version) => {
-                tx.send(Message::NewSubStep(
-                    String::from("Getting right java version"),
-                    2,
-                    5,
-                ))
+            Some(version) => {
+                install_java_version(component, os_folder, version.manifest, online_version, tx)
+            }
+        }
+    }
+}
+
+fn check_runtime(
+    provider: &dyn RuntimeProvider,
+    java_v_type: String,
+    major_version: u8,
+    online_version: String,
+    tx: Sender<Message>,
+) -> Option<Sender<Message>> {
+    tx.send(Message::NewSubStep(
+        String::from("Checking if required version is installed"),
+        3,
+        5,
+    ))
+    .unwrap_or(());
+    match path::get_java_folder_path_sub(&java_v_type) {
+        None => {
+            tx.send(Message::Error(format!(
+                "Unable to get java_folder_path_sub from type '{}'",
+                java_v_type
+            )))
+            .unwrap_or(());
+            None
+        }
+        Some(j_folder) => match path::get_java_folder_path(&java_v_type) {
+            None => {
+                tx.send(Message::Error(format!(
+                    "Unable to get java_folder_path from type '{}'",
+                    java_v_type
+                )))
                 .unwrap_or(());
-                let java_v_type = match version_manifest.java_version {
-                    None => String::from("jre-legacy"),
-                    Some(ver) => ver.component,
-                };
-                match os_version.get_java_version(&java_v_type) {
-                    None => {
-                        tx.send(Message::Error(format!(
-                            "Unable to get java_version from type '{}'",
-                            java_v_type
-                        )))
-                        .unwrap_or(());
-                        None
-                    }
-                    Some(versions) => match versions.get(0) {
-                        None => {
-                            tx.send(Message::Error(String::from(
-                                "Unable to get first java version",
-                            )))
-                            .unwrap_or(());
-                            None
-                        }
-                        Some(version) => {
-                            let online_version = version.clone().version.name;
-                            tx.send(Message::NewSubStep(
-                                String::from("Checking if required version is installed"),
-                                3,
-                                5,
-                            ))
-                            .unwrap_or(());
-                            match path::get_java_folder_path_sub(&java_v_type) {
-                                None => {
-                                    tx.send(Message::Error(format!(
-                                        "Unable to get java_folder_path_sub from type '{}'",
-                                        java_v_type
-                                    )))
-                                    .unwrap_or(());
-                                    None
-                                }
-                                Some(j_folder) => match path::get_java_folder_path(&java_v_type) {
-                                    None => {
-                                        tx.send(Message::Error(format!(
-                                            "Unable to get java_folder_path from type '{}'",
-                                            java_v_type
-                                        )))
-                                        .unwrap_or(());
-                                        None
-                                    }
-                                    Some(os_fol) => check_if_install_is_needed(
-                                        j_folder,
-                                        os_fol,
-                                        java_v_type,
-                                        version,
-                                        online_version,
-                                        tx,
-                                    ),
-                                },
-                            }
-                        }
-                    },
-                }
+                None
             }
+            Some(os_fol) => check_if_install_is_needed(
+                provider,
+                j_folder,
+                os_fol,
+                java_v_type,
+                major_version,
+                online_version,
+                tx,
+            ),
         },
     }
 }
 
+// Used when the launcher can't provide a runtime for this version on this platform.
+fn fallback_to_system_jvm(
+    version_manifest: &version::Main,
+    error: String,
+    tx: Sender<Message>,
+) -> Option<Sender<Message>> {
+    match jvm::find_jvm_for_version(version_manifest.get_java_major_version()) {
+        Err(err) => {
+            tx.send(Message::Error(format!("{} and {}", error, err)))
+                .unwrap_or(());
+            None
+        }
+        Ok(system_jvm) => use_system_jvm(system_jvm, tx),
+    }
+}
+
+fn use_system_jvm(system_jvm: jvm::SystemJvm, tx: Sender<Message>) -> Option<Sender<Message>> {
+    tx.send(Message::NewSubStep(
+        format!(
+            "Using system Java {} ({})",
+            system_jvm.version,
+            system_jvm.executable.display()
+        ),
+        5,
+        5,
+    ))
+    .unwrap_or(());
+    Some(tx)
+}
+
 fn check_if_install_is_needed(
+    provider: &dyn RuntimeProvider,
     j_folder: PathBuf,
     os_fol: PathBuf,
     java_v_type: String,
-    version: &Version,
+    major_version: u8,
     online_version: String,
     tx: Sender<Message>,
 ) -> Option<Sender<Message>> {
@@ -177,39 +406,42 @@ fn check_if_install_is_needed(
                     Ok(_) => {
                         if online_version != v_content {
                             install(
+                                provider,
                                 &java_v_type,
+                                major_version,
                                 os_fol,
-                                version.clone().manifest,
                                 online_version,
                                 tx,
                             )
                         } else {
-                            // install(&java_v_type, os_fol, version.clone().manifest, online_version, tx)
                             Some(tx)
                         }
                     }
                     Err(_) => install(
+                        provider,
                         &java_v_type,
+                        major_version,
                         os_fol,
-                        version.clone().manifest,
                         online_version,
                         tx,
                     ),
                 }
             }
             Err(_) => install(
+                provider,
                 &java_v_type,
+                major_version,
                 os_fol,
-                version.clone().manifest,
                 online_version,
                 tx,
             ),
         }
     } else {
         install(
+            provider,
             &java_v_type,
+            major_version,
             os_fol,
-            version.clone().manifest,
             online_version,
             tx,
         )
@@ -226,19 +458,20 @@ fn get_java_folder_path_sub(version_manifest: &version::Main) -> Option<PathBuf>
 }
 
 fn install(
+    provider: &dyn RuntimeProvider,
     java_v_type: &str,
+    major_version: u8,
     os_fol: PathBuf,
-    manifest: java_versions::Manifest,
     online_version: String,
     tx: Sender<Message>,
 ) -> Option<Sender<Message>> {
     tx.send(Message::NewSubStep(
-        String::from("Installing missing files"),
+        format!("Installing missing files from {}", provider.get_name()),
         4,
         5,
     ))
     .unwrap_or(());
-    match install_java_version(&java_v_type, os_fol, manifest, online_version, tx) {
+    match provider.install_runtime(java_v_type, major_version, os_fol, online_version, tx) {
         None => None,
         Some(tx) => {
             tx.send(Message::NewSubStep(String::from("Done"), 5, 5))
@@ -485,8 +718,9 @@ pub fn get_java_ex_for_os() -> &'static str {
     }
 }
 
-fn get_java_version_manifest() -> Result<java_versions::Main, String> {
-    match path::read_file_from_url_to_string(&"https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json".to_string()) {
+pub fn get_java_version_manifest() -> Result<java_versions::Main, String> {
+    let manifest_url = config::read_config().unwrap_or_default().java_manifest_url;
+    match path::read_file_from_url_to_string(&manifest_url) {
         Ok(body) => {
             match java_versions::parse_java_versions_manifest(&body) {
                 Ok(manifest) => Ok(manifest),
@@ -554,29 +788,13 @@ fn create_symlink(
             }
 
             let path_parts: Vec<&str> = path_name.split('/').collect();
-            let target_parts: Vec<&str> = target.split('/').collect();
-
             let mut path_buf = PathBuf::from(v_folder);
             for path_part in path_parts {
                 path_buf = path_buf.join(path_part);
             }
 
-            let mut target_buf = path_buf.clone();
-            for path_part in target_parts {
-                if path_part == ".." {
-                    target_buf = match target_buf.parent() {
-                        None => target_buf,
-                        Some(p) => match p.to_path_buf().parent() {
-                            None => p.to_path_buf(),
-                            Some(p2) => p2.to_path_buf(),
-                        },
-                    };
-                } else {
-                    target_buf = target_buf.join(path_part);
-                }
-            }
-
-            match symlink(target_buf, path_buf) {
+            // Targets are relative to the link's folder, keep them that way.
+            match symlink(target, path_buf) {
                 Ok(_) => Some(()),
                 Err(err) => {
                     tx.send(Message::Error(format!("Failed to create symlink: {}", err)))
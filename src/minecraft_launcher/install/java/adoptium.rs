@@ -0,0 +1,547 @@
+use crate::minecraft_launcher::app::download_tab::Message;
+use crate::minecraft_launcher::install::java::{replace_folder, RuntimeProvider};
+use crate::minecraft_launcher::{config, path, utils};
+
+use flate2::read::GzDecoder;
+use serde_derive::Deserialize;
+use std::cell::RefCell;
+use std::env::consts;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::Sender;
+use tar::Archive;
+
+const LATEST_ASSETS: &str = "/v3/assets/latest/:feature_version/hotspot?architecture=:arch&image_type=:image_type&os=:os&vendor=eclipse";
+
+/// Installs Eclipse Temurin builds from an Adoptium compatible API, for platforms Mojang
+/// doesn't ship runtimes for.
+pub struct AdoptiumRuntimeProvider {
+    api_url: String,
+    // The release found by get_runtime_version is the one installed right after.
+    release: RefCell<Option<(u8, Release)>>,
+}
+
+impl AdoptiumRuntimeProvider {
+    pub fn new() -> AdoptiumRuntimeProvider {
+        AdoptiumRuntimeProvider::with_api_url(
+            config::read_config().unwrap_or_default().adoptium_api_url,
+        )
+    }
+
+    fn with_api_url(api_url: String) -> AdoptiumRuntimeProvider {
+        AdoptiumRuntimeProvider {
+            api_url,
+            release: RefCell::new(None),
+        }
+    }
+
+    fn get_release(&self, major_version: u8) -> Result<Option<Release>, String> {
+        if let Some((cached_version, release)) = &*self.release.borrow() {
+            if *cached_version == major_version {
+                return Ok(Some(release.clone()));
+            }
+        }
+
+        let release = self.fetch_release(major_version)?;
+        if let Some(release) = &release {
+            *self.release.borrow_mut() = Some((major_version, release.clone()));
+        }
+
+        Ok(release)
+    }
+
+    fn fetch_release(&self, major_version: u8) -> Result<Option<Release>, String> {
+        let (os, arch) = match (get_os_name(), get_arch_name()) {
+            (Some(os), Some(arch)) => (os, arch),
+            _ => return Ok(None),
+        };
+
+        // Some feature versions (16 for example) don't have any JRE build.
+        for image_type in &["jre", "jdk"] {
+            let url = self.api_url.trim_end_matches('/').to_string()
+                + &LATEST_ASSETS
+                    .replace(":feature_version", major_version.to_string().as_str())
+                    .replace(":arch", arch)
+                    .replace(":image_type", image_type)
+                    .replace(":os", os);
+
+            let body = path::read_file_from_url_to_string(&url)?;
+
+            // Missing builds are reported with an error object instead of an empty list.
+            if let Ok(releases) = get_releases(&body) {
+                if let Some(release) = releases.into_iter().next() {
+                    return Ok(Some(release));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl RuntimeProvider for AdoptiumRuntimeProvider {
+    fn get_name(&self) -> String {
+        "Adoptium".to_string()
+    }
+
+    fn get_runtime_version(
+        &self,
+        _component: &str,
+        major_version: u8,
+    ) -> Result<Option<String>, String> {
+        Ok(self
+            .get_release(major_version)?
+            .map(|release| release.release_name))
+    }
+
+    fn install_runtime(
+        &self,
+        component: &str,
+        major_version: u8,
+        os_folder: PathBuf,
+        online_version: String,
+        tx: Sender<Message>,
+    ) -> Option<Sender<Message>> {
+        let release = match self.get_release(major_version) {
+            Ok(Some(release)) => release,
+            Ok(None) => {
+                tx.send(Message::Error(format!(
+                    "Adoptium doesn't have any Java {} for this platform",
+                    major_version
+                )))
+                .unwrap_or(());
+                return None;
+            }
+            Err(err) => {
+                tx.send(Message::Error(err)).unwrap_or(());
+                return None;
+            }
+        };
+
+        let package = release.binary.package;
+        let archive_path = os_folder.join(&package.name);
+
+        tx.send(Message::NewSubSubStep(package.name.clone(), 1, 3))
+            .unwrap_or(());
+        if archive_path.exists() {
+            fs::remove_file(&archive_path).unwrap_or(());
+        }
+        if let Err(err) = path::download_file_to(&package.link, &archive_path) {
+            tx.send(Message::Error(err)).unwrap_or(());
+            return None;
+        }
+
+        match archive_path.metadata() {
+            Ok(meta) if meta.len() == package.size => {}
+            _ => {
+                tx.send(Message::Error(format!(
+                    "Downloaded archive {} is corrupted",
+                    package.name
+                )))
+                .unwrap_or(());
+                fs::remove_file(&archive_path).unwrap_or(());
+                return None;
+            }
+        }
+
+        // The runtime runs whatever code it contains, the size alone isn't enough.
+        match utils::get_file_sha256(&archive_path) {
+            Ok(checksum) if checksum.eq_ignore_ascii_case(&package.checksum) => {}
+            Ok(checksum) => {
+                tx.send(Message::Error(format!(
+                    "Checksum of {} is {}, expected {}",
+                    package.name, checksum, package.checksum
+                )))
+                .unwrap_or(());
+                fs::remove_file(&archive_path).unwrap_or(());
+                return None;
+            }
+            Err(err) => {
+                tx.send(Message::Error(err)).unwrap_or(());
+                fs::remove_file(&archive_path).unwrap_or(());
+                return None;
+            }
+        }
+
+        // The runtime in place keeps working until the new one is fully extracted next to it.
+        let java_folder = os_folder.join(component);
+        let new_java_folder = os_folder.join(format!("{}.new", component));
+        if new_java_folder.exists() {
+            fs::remove_dir_all(&new_java_folder).unwrap_or(());
+        }
+
+        // The launcher expects the macOS runtime to be a bundle, like Mojang's.
+        let target = match consts::OS {
+            "macos" => new_java_folder.join("jre.bundle"),
+            &_ => new_java_folder.clone(),
+        };
+
+        tx.send(Message::NewSubSubStep(
+            format!("Extracting {}", package.name),
+            2,
+            3,
+        ))
+        .unwrap_or(());
+        let extracted = if package.name.ends_with(".zip") {
+            extract_zip(&archive_path, &target)
+        } else {
+            extract_tar_gz(&archive_path, &target)
+        };
+        fs::remove_file(&archive_path).unwrap_or(());
+
+        if let Err(err) = extracted.and_then(|_| replace_folder(&new_java_folder, &java_folder)) {
+            tx.send(Message::Error(format!(
+                "Failed to extract {}: {}",
+                package.name, err
+            )))
+            .unwrap_or(());
+            fs::remove_dir_all(&new_java_folder).unwrap_or(());
+            return None;
+        }
+
+        tx.send(Message::NewSubSubStep(".version".to_string(), 3, 3))
+            .unwrap_or(());
+        match File::create(os_folder.join(".version")) {
+            Ok(mut v_file) => match v_file.write_all(online_version.as_bytes()) {
+                Ok(_) => Some(tx),
+                Err(_) => {
+                    tx.send(Message::Error(
+                        "Failed to write to .version file".to_string(),
+                    ))
+                    .unwrap_or(());
+                    None
+                }
+            },
+            Err(err) => {
+                tx.send(Message::Error(format!(
+                    "Failed to create .version file: {}",
+                    err
+                )))
+                .unwrap_or(());
+                None
+            }
+        }
+    }
+}
+
+fn extract_tar_gz(archive_path: &Path, target: &Path) -> Result<(), String> {
+    let file = File::open(archive_path).map_err(|err| err.to_string())?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+
+    for entry in archive.entries().map_err(|err| err.to_string())? {
+        let mut entry = entry.map_err(|err| err.to_string())?;
+        let entry_path = entry.path().map_err(|err| err.to_string())?.into_owned();
+
+        if let Some(out_path) = get_output_path(&entry_path, target) {
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            entry.unpack(&out_path).map_err(|err| err.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip(archive_path: &Path, target: &Path) -> Result<(), String> {
+    let file = File::open(archive_path).map_err(|err| err.to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|err| err.to_string())?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|err| err.to_string())?;
+
+        let out_path = match file
+            .enclosed_name()
+            .and_then(|entry_path| get_output_path(entry_path, target))
+        {
+            Some(out_path) => out_path,
+            None => continue,
+        };
+
+        if file.is_dir() {
+            fs::create_dir_all(&out_path).map_err(|err| err.to_string())?;
+        } else {
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            let mut out_file = File::create(&out_path).map_err(|err| err.to_string())?;
+            io::copy(&mut file, &mut out_file).map_err(|err| err.to_string())?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if let Some(mode) = file.unix_mode() {
+                    fs::set_permissions(&out_path, fs::Permissions::from_mode(mode))
+                        .map_err(|err| err.to_string())?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Archives contain a single top folder named after the release ("jdk-17.0.2+8-jre").
+fn get_output_path(entry_path: &Path, target: &Path) -> Option<PathBuf> {
+    let mut out_path = target.to_path_buf();
+    let mut relative = false;
+
+    for component in entry_path.components().skip(1) {
+        match component {
+            Component::Normal(part) => {
+                out_path = out_path.join(part);
+                relative = true;
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if relative {
+        Some(out_path)
+    } else {
+        None
+    }
+}
+
+fn get_os_name() -> Option<&'static str> {
+    match consts::OS {
+        "linux" => {
+            if cfg!(target_env = "musl") {
+                Some("alpine-linux")
+            } else {
+                Some("linux")
+            }
+        }
+        "macos" => Some("mac"),
+        "windows" => Some("windows"),
+        &_ => None,
+    }
+}
+
+fn get_arch_name() -> Option<&'static str> {
+    match consts::ARCH {
+        "x86_64" => Some("x64"),
+        "x86" => Some("x32"),
+        "aarch64" => Some("aarch64"),
+        "arm" => Some("arm"),
+        &_ => None,
+    }
+}
+
+fn get_releases(string: &str) -> Result<Vec<Release>, String> {
+    match serde_json::from_str(string) {
+        Ok(releases) => Ok(releases),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+#[derive(Deserialize, Clone)]
+struct Release {
+    pub binary: Binary,
+    pub release_name: String,
+}
+
+#[derive(Deserialize, Clone)]
+struct Binary {
+    pub package: Package,
+}
+
+#[derive(Deserialize, Clone)]
+struct Package {
+    pub link: String,
+    pub name: String,
+    pub size: u64,
+    // SHA-256 of the archive.
+    pub checksum: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use sha2::{Digest, Sha256};
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::thread;
+    use uuid::Uuid;
+
+    // Stands in for the Adoptium API: the assets query gets `release`, anything else the
+    // archive. Returns the base url and the number of asset queries.
+    fn serve(release: String, archive: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let queries = Arc::new(AtomicUsize::new(0));
+
+        let counter = queries.clone();
+        let release = release.replace(":base_url", &base_url);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                    header.clear();
+                }
+
+                let body = if request_line.contains("/v3/assets/latest/") {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    release.as_bytes().to_vec()
+                } else {
+                    archive.clone()
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+
+        (base_url, queries)
+    }
+
+    fn get_release_json(archive: &[u8], checksum: &str) -> String {
+        format!(
+            r#"[{{
+                "binary": {{
+                    "package": {{
+                        "link": ":base_url/OpenJDK17U-jre.tar.gz",
+                        "name": "OpenJDK17U-jre.tar.gz",
+                        "size": {},
+                        "checksum": "{}"
+                    }}
+                }},
+                "release_name": "jdk-17.0.2+8"
+            }}]"#,
+            archive.len(),
+            checksum
+        )
+    }
+
+    fn get_archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+
+        let content = b"#!/bin/sh\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "jdk-17.0.2+8-jre/bin/java", &content[..])
+            .unwrap();
+
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn get_sha256(bytes: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.write_all(bytes).unwrap();
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn get_temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!(
+            "rusty-craft-adoptium-{}",
+            Uuid::new_v4().to_simple()
+        ));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    // A runtime installed before, as the launcher would find it.
+    fn get_previous_java(os_folder: &Path) -> PathBuf {
+        let bin_folder = os_folder.join("java-runtime-beta").join("bin");
+        fs::create_dir_all(&bin_folder).unwrap();
+        fs::write(bin_folder.join("previous"), b"").unwrap();
+        bin_folder.join("previous")
+    }
+
+    #[test]
+    fn release_is_fetched_once() {
+        let archive = get_archive();
+        let (base_url, queries) = serve(get_release_json(&archive, &get_sha256(&archive)), archive);
+        let provider = AdoptiumRuntimeProvider::with_api_url(base_url);
+
+        assert_eq!(
+            provider.get_runtime_version("java-runtime-beta", 17),
+            Ok(Some(String::from("jdk-17.0.2+8")))
+        );
+        assert!(provider.get_release(17).unwrap().is_some());
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn install_checks_the_checksum() {
+        let archive = get_archive();
+        let (base_url, _) = serve(get_release_json(&archive, &get_sha256(&archive)), archive);
+        let provider = AdoptiumRuntimeProvider::with_api_url(base_url);
+        let os_folder = get_temp_folder();
+        let previous_java = get_previous_java(&os_folder);
+
+        let (tx, _rx) = channel();
+        let installed = provider.install_runtime(
+            "java-runtime-beta",
+            17,
+            os_folder.clone(),
+            String::from("jdk-17.0.2+8"),
+            tx,
+        );
+
+        assert!(installed.is_some());
+        assert!(os_folder.join(".version").is_file());
+        assert!(!previous_java.exists());
+        assert!(!os_folder.join("java-runtime-beta.new").exists());
+        if consts::OS != "macos" {
+            assert!(os_folder
+                .join("java-runtime-beta")
+                .join("bin")
+                .join("java")
+                .is_file());
+        }
+        fs::remove_dir_all(os_folder).unwrap_or(());
+    }
+
+    #[test]
+    fn install_rejects_a_wrong_checksum() {
+        let archive = get_archive();
+        let (base_url, _) = serve(get_release_json(&archive, &"0".repeat(64)), archive);
+        let provider = AdoptiumRuntimeProvider::with_api_url(base_url);
+        let os_folder = get_temp_folder();
+        let previous_java = get_previous_java(&os_folder);
+
+        let (tx, rx) = channel();
+        let installed = provider.install_runtime(
+            "java-runtime-beta",
+            17,
+            os_folder.clone(),
+            String::from("jdk-17.0.2+8"),
+            tx,
+        );
+
+        assert!(installed.is_none());
+        assert!(rx
+            .try_iter()
+            .any(|message| matches!(message, Message::Error(err) if err.starts_with("Checksum"))));
+        assert!(previous_java.is_file());
+        assert!(!os_folder.join("java-runtime-beta.new").exists());
+        assert!(!os_folder.join(".version").exists());
+        fs::remove_dir_all(os_folder).unwrap_or(());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::minecraft_launcher::modding::ModLoaderInstaller;
+use crate::minecraft_launcher::{path, profiles};
+
+use chrono::{SecondsFormat, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+const INSTANCE_FILE: &str = "instance.json";
+const INSTANCE_FOLDERS: [&str; 4] = ["mods", "config", "saves", "resourcepacks"];
+
+/// A game directory of its own, bound to a version and a mod loader. Libraries, assets and
+/// versions are still shared through `.minecraft`.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Instance {
+    pub name: String,
+    #[serde(rename = "minecraftVersion")]
+    pub minecraft_version: String,
+    pub loader: String,
+    #[serde(rename = "loaderVersion", skip_serializing_if = "Option::is_none")]
+    pub loader_version: Option<String>,
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    pub created: String,
+}
+
+impl Instance {
+    pub fn get_game_dir(&self) -> PathBuf {
+        get_instances_folder().join(&self.name)
+    }
+
+    fn write(&self) -> Result<(), String> {
+        let body = match serde_json::to_string_pretty(self) {
+            Ok(body) => body,
+            Err(err) => return Err(format!("Unable to serialize instance: {}", err)),
+        };
+
+        match File::create(self.get_game_dir().join(INSTANCE_FILE)) {
+            Ok(mut file) => match file.write_all(body.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(format!("Unable to write {}: {}", INSTANCE_FILE, err)),
+            },
+            Err(err) => Err(format!("Unable to create {}: {}", INSTANCE_FILE, err)),
+        }
+    }
+}
+
+pub fn get_instances_folder() -> PathBuf {
+    match path::get_minecraft_sub_folder("instances") {
+        None => path::get_minecraft_directory().join("instances"),
+        Some(instances_folder) => instances_folder,
+    }
+}
+
+pub fn list_instances() -> Vec<Instance> {
+    let mut instances: Vec<Instance> = match fs::read_dir(get_instances_folder()) {
+        Ok(read_dir) => read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| read_instance(&entry.path().join(INSTANCE_FILE)))
+            .collect(),
+        Err(_) => vec![],
+    };
+
+    instances.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+    instances
+}
+
+/// Creates the instance folders and a launcher profile pointing at them.
+pub fn create_instance(
+    minecraft_version: String,
+    loader: &dyn ModLoaderInstaller,
+    loader_version: Option<String>,
+) -> Result<Instance, String> {
+    let version_id = match &loader_version {
+        None => loader.get_profile_name_for_mc_version(minecraft_version.clone()),
+        Some(loader_version) => loader
+            .get_profile_name_for_loader_version(minecraft_version.clone(), loader_version.clone()),
+    };
+
+    let instance = Instance {
+        name: get_free_name(&version_id),
+        minecraft_version,
+        loader: loader.get_name(),
+        loader_version,
+        version_id,
+        profile_id: Uuid::new_v4().to_simple().to_string(),
+        created: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+    };
+
+    let game_dir = instance.get_game_dir();
+    for folder in INSTANCE_FOLDERS.iter() {
+        if let Err(err) = fs::create_dir_all(game_dir.join(folder)) {
+            return Err(format!(
+                "Unable to create instance folder {}: {}",
+                folder, err
+            ));
+        }
+    }
+
+    // Starting with the shared options keeps key binds and video settings.
+    let shared_options = path::get_minecraft_directory().join("options.txt");
+    if shared_options.exists() {
+        fs::copy(shared_options, game_dir.join("options.txt")).unwrap_or(0);
+    }
+
+    instance.write()?;
+    profiles::add_instance_profile(&instance)?;
+
+    Ok(instance)
+}
+
+pub fn rename_instance(instance: &Instance, name: &str) -> Result<Instance, String> {
+    let name = name.trim();
+
+    if !is_valid_name(name) {
+        return Err(format!("'{}' can't be used as a folder name", name));
+    }
+    if get_instances_folder().join(name).exists() {
+        return Err(format!("An instance named {} already exists", name));
+    }
+
+    let mut renamed = instance.clone();
+    renamed.name = name.to_string();
+
+    if let Err(err) = fs::rename(instance.get_game_dir(), renamed.get_game_dir()) {
+        return Err(format!("Unable to rename instance folder: {}", err));
+    }
+
+    renamed.write()?;
+    profiles::add_instance_profile(&renamed)?;
+
+    Ok(renamed)
+}
+
+pub fn delete_instance(instance: &Instance) -> Result<(), String> {
+    if let Err(err) = fs::remove_dir_all(instance.get_game_dir()) {
+        return Err(format!("Unable to remove instance folder: {}", err));
+    }
+
+    profiles::remove_profile(&instance.profile_id)
+}
+
+fn read_instance(instance_file: &Path) -> Option<Instance> {
+    let mut body = String::new();
+
+    match File::open(instance_file) {
+        Ok(mut file) => match file.read_to_string(&mut body) {
+            Ok(_) => serde_json::from_str(&body).ok(),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    }
+}
+
+fn get_free_name(base: &str) -> String {
+    let base: String = base
+        .chars()
+        .map(|c| if is_valid_char(c) { c } else { '_' })
+        .collect();
+
+    let mut name = base.clone();
+    let mut index = 2;
+
+    while get_instances_folder().join(&name).exists() {
+        name = format!("{}-{}", base, index);
+        index += 1;
+    }
+
+    name
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && name.chars().all(is_valid_char)
+}
+
+fn is_valid_char(c: char) -> bool {
+    !c.is_control() && !"/\\:*?\"<>|".contains(c)
+}
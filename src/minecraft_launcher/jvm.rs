@@ -0,0 +1,324 @@
+use crate::minecraft_launcher::install::java;
+use crate::minecraft_launcher::path;
+
+use directories::BaseDirs;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::env::consts;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+
+// Versions made for Java 16 and newer also run on later releases, older ones (LaunchWrapper,
+// everything before 1.17) crash on Java 9+.
+const FORWARD_COMPATIBLE_JAVA: u8 = 16;
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct SystemJvm {
+    pub executable: PathBuf,
+    pub version: String,
+    #[serde(rename = "majorVersion")]
+    pub major_version: u8,
+    pub arch: String,
+}
+
+// Probing starts a JVM, its result is kept until the executable changes.
+#[derive(Deserialize, Serialize)]
+struct ProbedJvm {
+    modified: u64,
+    jvm: SystemJvm,
+}
+
+/// Looks for JVMs installed outside of the launcher and probes the ones not probed yet.
+pub fn discover_jvms() -> Vec<SystemJvm> {
+    let mut jvms: Vec<SystemJvm> = Vec::new();
+    let mut probed: Vec<PathBuf> = Vec::new();
+
+    let old_cache = read_probe_cache();
+    let mut cache: HashMap<String, ProbedJvm> = HashMap::new();
+
+    for executable in get_candidate_executables() {
+        if !executable.is_file() {
+            continue;
+        }
+
+        // The same JVM is often reachable through JAVA_HOME, PATH and /usr/lib/jvm.
+        let canonical = match fs::canonicalize(&executable) {
+            Ok(canonical) => canonical,
+            Err(_) => executable.clone(),
+        };
+        if probed.contains(&canonical) {
+            continue;
+        }
+        probed.push(canonical.clone());
+
+        let key = canonical.display().to_string();
+        let modified = get_modified(&canonical);
+
+        let jvm = match old_cache.get(&key) {
+            Some(cached) if cached.modified == modified && cached.jvm.executable == executable => {
+                Some(cached.jvm.clone())
+            }
+            _ => probe_jvm(&executable),
+        };
+
+        if let Some(jvm) = jvm {
+            cache.insert(
+                key,
+                ProbedJvm {
+                    modified,
+                    jvm: jvm.clone(),
+                },
+            );
+            jvms.push(jvm);
+        }
+    }
+
+    write_probe_cache(&cache);
+
+    jvms
+}
+
+/// Picks a JVM of the host architecture with the requested major version. Only versions made
+/// for Java 16 or newer fall back to the closest newer JVM.
+pub fn find_jvm_for_version(major_version: u8) -> Result<SystemJvm, String> {
+    let jvms: Vec<SystemJvm> = discover_jvms()
+        .into_iter()
+        .filter(|jvm| is_host_arch(&jvm.arch))
+        .collect();
+
+    select_jvm(jvms, major_version)
+}
+
+fn select_jvm(mut jvms: Vec<SystemJvm>, major_version: u8) -> Result<SystemJvm, String> {
+    jvms.sort_by(|a, b| a.major_version.cmp(&b.major_version));
+
+    if let Some(jvm) = jvms.iter().find(|jvm| jvm.major_version == major_version) {
+        return Ok(jvm.clone());
+    }
+    if major_version >= FORWARD_COMPATIBLE_JAVA {
+        if let Some(jvm) = jvms.iter().find(|jvm| jvm.major_version > major_version) {
+            return Ok(jvm.clone());
+        }
+    }
+
+    let mut found: Vec<String> = jvms
+        .iter()
+        .map(|jvm| jvm.major_version.to_string())
+        .collect();
+    found.dedup();
+
+    Err(match found.is_empty() {
+        true => format!("no system Java {} was found", major_version),
+        false => format!(
+            "no system Java {} was found, Java {} can't run this version",
+            major_version,
+            found.join(", ")
+        ),
+    })
+}
+
+pub fn probe_jvm(executable: &Path) -> Option<SystemJvm> {
+    let output = match Command::new(executable)
+        .arg("-XshowSettings:properties")
+        .arg("-version")
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return None,
+    };
+
+    // Settings are printed on stderr.
+    let settings = String::from_utf8_lossy(&output.stderr);
+
+    let mut version = None;
+    let mut arch = None;
+
+    for line in settings.lines() {
+        let parts: Vec<&str> = line.splitn(2, '=').collect();
+        if parts.len() != 2 {
+            continue;
+        }
+
+        match parts[0].trim() {
+            "java.version" => version = Some(parts[1].trim().to_string()),
+            "os.arch" => arch = Some(parts[1].trim().to_string()),
+            _ => {}
+        }
+    }
+
+    match (version, arch) {
+        (Some(version), Some(arch)) => {
+            parse_major_version(&version).map(|major_version| SystemJvm {
+                executable: executable.to_path_buf(),
+                version,
+                major_version,
+                arch,
+            })
+        }
+        _ => None,
+    }
+}
+
+// "1.8.0_292" is Java 8, "17.0.2" and "17" are Java 17.
+fn parse_major_version(version: &str) -> Option<u8> {
+    let parts: Vec<&str> = version
+        .split(|c| c == '.' || c == '_' || c == '-' || c == '+')
+        .collect();
+
+    let major = match parts.get(0) {
+        Some(&"1") => parts.get(1),
+        other => other,
+    };
+
+    major.and_then(|major| major.parse::<u8>().ok())
+}
+
+fn get_probe_cache_path() -> PathBuf {
+    match path::get_launcher_folder() {
+        None => path::get_minecraft_directory().join("rusty-craft-jvms.json"),
+        Some(launcher_folder) => launcher_folder.join("jvms.json"),
+    }
+}
+
+// A missing or broken cache only means probing again.
+fn read_probe_cache() -> HashMap<String, ProbedJvm> {
+    fs::read_to_string(get_probe_cache_path())
+        .ok()
+        .and_then(|body| serde_json::from_str(&body).ok())
+        .unwrap_or_default()
+}
+
+fn write_probe_cache(cache: &HashMap<String, ProbedJvm>) {
+    if let Ok(body) = serde_json::to_string_pretty(cache) {
+        fs::write(get_probe_cache_path(), body).unwrap_or(());
+    }
+}
+
+fn get_modified(executable: &Path) -> u64 {
+    fs::metadata(executable)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_secs())
+}
+
+fn is_host_arch(arch: &str) -> bool {
+    normalize_arch(arch) == normalize_arch(consts::ARCH)
+}
+
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "amd64" | "x86_64" | "x64" => "x86_64",
+        "x86" | "i386" | "i686" => "x86",
+        "aarch64" | "arm64" => "aarch64",
+        other => other,
+    }
+}
+
+fn get_candidate_executables() -> Vec<PathBuf> {
+    let java_ex = java::get_java_ex_for_os();
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        candidates.push(PathBuf::from(java_home).join("bin").join(java_ex));
+    }
+
+    if let Some(path) = env::var_os("PATH") {
+        for folder in env::split_paths(&path) {
+            candidates.push(folder.join(java_ex));
+        }
+    }
+
+    for jvm_home in list_sub_folders(Path::new("/usr/lib/jvm")) {
+        candidates.push(jvm_home.join("bin").join(java_ex));
+        candidates.push(jvm_home.join("jre").join("bin").join(java_ex));
+    }
+
+    let sdkman_folder = match env::var_os("SDKMAN_DIR") {
+        Some(sdkman_dir) => Some(PathBuf::from(sdkman_dir)),
+        None => BaseDirs::new().map(|base_dirs| base_dirs.home_dir().join(".sdkman")),
+    };
+
+    if let Some(sdkman_folder) = sdkman_folder {
+        for jvm_home in list_sub_folders(&sdkman_folder.join("candidates").join("java")) {
+            candidates.push(jvm_home.join("bin").join(java_ex));
+        }
+    }
+
+    candidates
+}
+
+pub(crate) fn list_sub_folders(folder: &Path) -> Vec<PathBuf> {
+    match fs::read_dir(folder) {
+        Ok(read_dir) => read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jvm(major_version: u8) -> SystemJvm {
+        SystemJvm {
+            executable: PathBuf::from(format!("/usr/lib/jvm/java-{}/bin/java", major_version)),
+            version: major_version.to_string(),
+            major_version,
+            arch: String::from("amd64"),
+        }
+    }
+
+    #[test]
+    fn major_versions() {
+        let cases = [
+            ("1.8.0_292", Some(8)),
+            ("1.8.0", Some(8)),
+            ("1.7.0_80", Some(7)),
+            ("11.0.12", Some(11)),
+            ("17", Some(17)),
+            ("17.0.2", Some(17)),
+            ("21+35", Some(21)),
+            ("21.0.1+12-LTS", Some(21)),
+            ("16-ea", Some(16)),
+            ("", None),
+            ("openjdk", None),
+        ];
+
+        for (version, expected) in cases.iter() {
+            assert_eq!(parse_major_version(version), *expected, "{}", version);
+        }
+    }
+
+    #[test]
+    fn jvm_selection() {
+        let cases: [(&[u8], u8, Option<u8>); 7] = [
+            (&[8, 11, 17], 8, Some(8)),
+            (&[11, 17], 8, None),
+            (&[17, 21], 17, Some(17)),
+            (&[21, 11], 17, Some(21)),
+            (&[11], 16, None),
+            (&[21, 17], 16, Some(17)),
+            (&[], 8, None),
+        ];
+
+        for (installed, required, expected) in cases.iter() {
+            let jvms = installed.iter().map(|major| jvm(*major)).collect();
+            assert_eq!(
+                select_jvm(jvms, *required)
+                    .ok()
+                    .map(|jvm| jvm.major_version),
+                *expected,
+                "Java {} with {:?} installed",
+                required,
+                installed
+            );
+        }
+    }
+}
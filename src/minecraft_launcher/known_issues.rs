@@ -0,0 +1,253 @@
+use crate::minecraft_launcher::path;
+
+use regex::Regex;
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const SHIPPED_ISSUES: &str = include_str!("../../resources/known_issues.json");
+
+// Versions without a java version in their manifest all run on Java 8.
+const DEFAULT_JAVA: u8 = 8;
+
+#[derive(Deserialize)]
+struct IssueDefinition {
+    id: String,
+    patterns: Vec<String>,
+    diagnosis: String,
+    fix: String,
+}
+
+struct KnownIssue {
+    definition: IssueDefinition,
+    patterns: Vec<Regex>,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Diagnosis {
+    pub id: String,
+    pub diagnosis: String,
+    pub fix: String,
+}
+
+/// Turns game output and crash reports into plain-language diagnoses.
+pub struct Analyzer {
+    issues: Vec<KnownIssue>,
+    expected_java: u8,
+    pub errors: Vec<String>,
+}
+
+impl Analyzer {
+    /// Loads the shipped issues, then the user's, which replace shipped ones sharing their id.
+    pub fn load(expected_java: Option<u8>) -> Analyzer {
+        let mut analyzer = Analyzer {
+            issues: vec![],
+            expected_java: expected_java.unwrap_or(DEFAULT_JAVA),
+            errors: vec![],
+        };
+
+        analyzer.add_definitions(SHIPPED_ISSUES, "shipped known issues");
+
+        let user_file = get_user_issues_path();
+        if let Ok(body) = fs::read_to_string(&user_file) {
+            analyzer.add_definitions(&body, &user_file.display().to_string());
+        }
+
+        analyzer
+    }
+
+    fn add_definitions(&mut self, body: &str, origin: &str) {
+        let definitions: Vec<IssueDefinition> = match serde_json::from_str(body) {
+            Ok(definitions) => definitions,
+            Err(err) => {
+                self.errors
+                    .push(format!("Unable to read {}: {}", origin, err));
+                return;
+            }
+        };
+
+        for definition in definitions {
+            let mut patterns = vec![];
+            for pattern in &definition.patterns {
+                match Regex::new(pattern) {
+                    Ok(regex) => patterns.push(regex),
+                    Err(err) => self.errors.push(format!(
+                        "Invalid pattern in known issue {}: {}",
+                        definition.id, err
+                    )),
+                }
+            }
+
+            self.issues
+                .retain(|issue| issue.definition.id != definition.id);
+            self.issues.push(KnownIssue {
+                definition,
+                patterns,
+            });
+        }
+    }
+
+    pub fn analyze(&self, text: &str) -> Vec<Diagnosis> {
+        let mut diagnoses = vec![];
+
+        for issue in &self.issues {
+            let (pattern, captures) = match issue
+                .patterns
+                .iter()
+                .find_map(|pattern| pattern.captures(text).map(|captures| (pattern, captures)))
+            {
+                Some(found) => found,
+                None => continue,
+            };
+
+            let mut values: HashMap<String, String> = HashMap::new();
+            for name in pattern.capture_names().flatten() {
+                if let Some(value) = captures.name(name) {
+                    values.insert(name.to_string(), value.as_str().to_string());
+                }
+            }
+            self.add_java_values(&mut values);
+
+            diagnoses.push(Diagnosis {
+                id: issue.definition.id.clone(),
+                diagnosis: fill_template(&issue.definition.diagnosis, &values),
+                fix: fill_template(&issue.definition.fix, &values),
+            });
+        }
+
+        diagnoses
+    }
+
+    // Class file versions start at 45 for Java 1.1, so Java 8 writes 52.
+    fn add_java_values(&self, values: &mut HashMap<String, String>) {
+        let required_java = match values
+            .get("classVersion")
+            .and_then(|class_version| class_version.parse::<u8>().ok())
+        {
+            Some(class_version) if class_version > 44 => class_version - 44,
+            _ => return,
+        };
+
+        values.insert(String::from("requiredJava"), required_java.to_string());
+        values.insert(String::from("expectedJava"), self.expected_java.to_string());
+        values.insert(
+            String::from("javaAdvice"),
+            if required_java > self.expected_java {
+                String::from(
+                    "The mod is made for a newer Minecraft version, use a build made for this one.",
+                )
+            } else {
+                format!(
+                    "The game ran on a Java older than {}, turn off preferSystemJava in the launcher \
+                     config or install a newer system Java.",
+                    self.expected_java
+                )
+            },
+        );
+    }
+}
+
+pub fn get_user_issues_path() -> PathBuf {
+    match path::get_launcher_folder() {
+        None => path::get_minecraft_directory().join("rusty-craft-known-issues.json"),
+        Some(launcher_folder) => launcher_folder.join("known_issues.json"),
+    }
+}
+
+fn fill_template(template: &str, values: &HashMap<String, String>) -> String {
+    let mut filled = template.to_string();
+
+    for (name, value) in values {
+        filled = filled.replace(&format!("{{{}}}", name), value);
+    }
+
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_shipped_analyzer(expected_java: u8) -> Analyzer {
+        let mut analyzer = Analyzer {
+            issues: vec![],
+            expected_java,
+            errors: vec![],
+        };
+        analyzer.add_definitions(SHIPPED_ISSUES, "shipped known issues");
+        analyzer
+    }
+
+    #[test]
+    fn shipped_issues() {
+        let cases = [
+            (
+                8,
+                "java.lang.UnsupportedClassVersionError: net/example/ExampleMod has been compiled by a more recent version of the Java Runtime (class file version 61.0), this version of the Java Runtime only recognizes class file versions up to 52.0",
+                "class-version",
+                "net/example/ExampleMod needs Java 17, this version is made for Java 8.",
+                "The mod is made for a newer Minecraft version, use a build made for this one.",
+            ),
+            (
+                17,
+                "Exception in thread \"main\" java.lang.UnsupportedClassVersionError: net/example/ExampleMod has been compiled by a more recent version of the Java Runtime (class file version 65.0), this version of the Java Runtime only recognizes class file versions up to 61.0",
+                "class-version",
+                "net/example/ExampleMod needs Java 21, this version is made for Java 17.",
+                "The mod is made for a newer Minecraft version, use a build made for this one.",
+            ),
+            (
+                8,
+                "java.lang.UnsupportedClassVersionError: net/minecraft/launchwrapper/Launch : Unsupported major.minor version 52.0",
+                "class-version",
+                "net/minecraft/launchwrapper/Launch needs Java 8, this version is made for Java 8.",
+                "The game ran on a Java older than 8, turn off preferSystemJava in the launcher config or install a newer system Java.",
+            ),
+            (
+                17,
+                "\t - Mod 'Sodium' (sodium) 0.4.10+build.27 requires version 0.75.1 or later of mod 'Fabric API' (fabric-api), which is missing!",
+                "fabric-missing-dependency",
+                "Sodium needs Fabric API, which isn't installed.",
+                "Install Fabric API for this Minecraft version in the mods folder.",
+            ),
+            (
+                8,
+                "net.fabricmc.loader.discovery.ModResolutionException: Could not find required mod: modmenu requires {fabric @ [>=0.28.0]}",
+                "fabric-missing-dependency",
+                "modmenu needs fabric, which isn't installed.",
+                "Install fabric for this Minecraft version in the mods folder.",
+            ),
+            (
+                17,
+                "Mixin apply for mod sodium failed sodium.mixins.json:core.MixinMinecraftClient -> net.minecraft.client.MinecraftClient: org.spongepowered.asm.mixin.injection.throwables.InvalidInjectionException",
+                "mixin-apply",
+                "The mixin sodium.mixins.json:core.MixinMinecraftClient couldn't be applied to net.minecraft.client.MinecraftClient.",
+                "The mod owning it is likely made for another Minecraft version or conflicts with another mod, update or remove it.",
+            ),
+            (
+                8,
+                "org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError: Mixin apply failed mixins.example.json:MixinTitleScreen -> net.minecraft.client.gui.screen.TitleScreen: org.spongepowered.asm.mixin.throwables.MixinApplyError",
+                "mixin-apply",
+                "The mixin mixins.example.json:MixinTitleScreen couldn't be applied to net.minecraft.client.gui.screen.TitleScreen.",
+                "The mod owning it is likely made for another Minecraft version or conflicts with another mod, update or remove it.",
+            ),
+        ];
+
+        for (i, (expected_java, line, id, diagnosis, fix)) in cases.iter().enumerate() {
+            let analyzer = get_shipped_analyzer(*expected_java);
+            assert!(analyzer.errors.is_empty(), "case {}", i);
+
+            let found: Vec<(String, String, String)> = analyzer
+                .analyze(line)
+                .into_iter()
+                .map(|found| (found.id, found.diagnosis, found.fix))
+                .collect();
+            assert_eq!(
+                found,
+                vec![(id.to_string(), diagnosis.to_string(), fix.to_string())],
+                "case {}",
+                i
+            );
+        }
+    }
+}
@@ -1,27 +1,264 @@
 use crate::minecraft_launcher::app::download_tab::Message;
 use crate::minecraft_launcher::arguments;
+use crate::minecraft_launcher::arguments::Platform;
 use crate::minecraft_launcher::manifest::version::{
     LibraryDownloadArtifact, Main, Rule, RuleAction,
 };
 use crate::minecraft_launcher::path;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::fs;
 use std::fs::File;
 use std::io;
+use std::io::Write;
 
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::sync::mpsc::Sender;
 
-pub fn main(java_path: PathBuf, args: Vec<String>) -> Child {
-    Command::new(java_path)
-        .current_dir(path::get_minecraft_directory())
-        .args(args)
+// Kept when the environment is cleared, the game can't open a window or find the JVM's
+// libraries without them.
+const UNIX_KEPT_VARIABLES: [&str; 7] = [
+    "PATH",
+    "HOME",
+    "USER",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XAUTHORITY",
+    "XDG_RUNTIME_DIR",
+];
+// Windows programs can't create sockets or find their settings without these.
+const WINDOWS_KEPT_VARIABLES: [&str; 11] = [
+    "PATH",
+    "PATHEXT",
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "TEMP",
+    "TMP",
+    "USERNAME",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+];
+
+#[derive(Clone)]
+pub struct LaunchCommand {
+    pub java_path: PathBuf,
+    pub game_dir: PathBuf,
+    pub args: Vec<String>,
+    pub wrapper: Vec<String>,
+    // Placeholders already filled, `None` removes the variable.
+    pub env: HashMap<String, Option<String>>,
+    pub clear_env: bool,
+    // Given to the hooks, not to the game.
+    pub hook_env: HashMap<String, String>,
+}
+
+pub fn main(command: &LaunchCommand) -> Result<Child, String> {
+    let mut process = match command.wrapper.split_first() {
+        None => Command::new(&command.java_path),
+        Some((wrapper, wrapper_args)) => {
+            let mut process = Command::new(wrapper);
+            process.args(wrapper_args).arg(&command.java_path);
+            process
+        }
+    };
+
+    if command.clear_env {
+        process.env_clear();
+        for name in get_kept_variables() {
+            if let Some(value) = env::var_os(name) {
+                process.env(name, value);
+            }
+        }
+    }
+    for (name, value) in &command.env {
+        match value {
+            None => process.env_remove(name),
+            Some(value) => process.env(name, value),
+        };
+    }
+
+    match process
+        .current_dir(&command.game_dir)
+        .args(&command.args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .spawn()
-        .expect("Unable to launch Minecraft!")
+    {
+        Ok(child) => Ok(child),
+        Err(err) => Err(format!("Unable to launch Minecraft: {}", err)),
+    }
+}
+
+/// Runs a pre-launch or post-exit hook through the shell, in the game directory.
+pub fn run_hook(hook: &str, command: &LaunchCommand, exit_code: Option<i32>) -> Result<(), String> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(hook);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(hook);
+        process
+    };
+
+    process
+        .current_dir(&command.game_dir)
+        .envs(&command.hook_env)
+        .stdin(Stdio::null());
+
+    if let Some(exit_code) = exit_code {
+        process.env("RUSTY_CRAFT_EXIT_CODE", exit_code.to_string());
+    }
+
+    match process.output() {
+        Err(err) => Err(format!("Unable to run '{}': {}", hook, err)),
+        Ok(output) => {
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "'{}' failed ({}): {}",
+                    hook,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+        }
+    }
+}
+
+/// Builds a standalone `launch.sh` starting the game like `main` would. The access token is
+/// read from `$ACCESS_TOKEN` unless `include_token` is set.
+pub fn export_script(command: &LaunchCommand, access_token: &str, include_token: bool) -> String {
+    let mut lines = vec![
+        String::from("#!/bin/sh"),
+        String::from("# Exported by rusty-craft"),
+        format!(
+            "cd {} || exit 1",
+            quote_shell(&command.game_dir.display().to_string())
+        ),
+    ];
+
+    // Set first, the environment variables can hold the token too.
+    if !access_token.is_empty() {
+        lines.push(if include_token {
+            format!("ACCESS_TOKEN={}", quote_shell(access_token))
+        } else {
+            String::from("ACCESS_TOKEN=\"${ACCESS_TOKEN:?Set ACCESS_TOKEN to your access token}\"")
+        });
+    }
+
+    let mut game_env: Vec<(&String, &Option<String>)> = command.env.iter().collect();
+    game_env.sort();
+
+    // With a cleared environment the variables are given through env -i instead.
+    let mut program: Vec<String> = vec![];
+    if command.clear_env {
+        program.push(String::from("env -i"));
+        for name in get_kept_variables() {
+            program.push(format!("{}=\"${{{}}}\"", name, name));
+        }
+        for (name, value) in game_env {
+            if let Some(value) = value {
+                program.push(format!("{}={}", name, quote_redacted(value, access_token)));
+            }
+        }
+    } else {
+        for (name, value) in game_env {
+            lines.push(match value {
+                None => format!("unset {}", name),
+                Some(value) => format!("export {}={}", name, quote_redacted(value, access_token)),
+            });
+        }
+    }
+
+    program.extend(command.wrapper.iter().map(|arg| quote_shell(arg)));
+    program.push(quote_shell(&command.java_path.display().to_string()));
+    lines.push(format!("exec {} \\", program.join(" ")));
+
+    for (i, arg) in command.args.iter().enumerate() {
+        let arg = quote_redacted(arg, access_token);
+
+        if i + 1 < command.args.len() {
+            lines.push(format!("  {} \\", arg));
+        } else {
+            lines.push(format!("  {}", arg));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Writes the exported script to `launch.sh` in the game directory. It is a POSIX shell
+/// script, so Windows isn't supported.
+pub fn write_script(
+    command: &LaunchCommand,
+    access_token: &str,
+    include_token: bool,
+) -> Result<PathBuf, String> {
+    if cfg!(windows) {
+        return Err(String::from(
+            "Launch scripts are shell scripts, they can't be exported on Windows",
+        ));
+    }
+
+    let script_path = command.game_dir.join("launch.sh");
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    // Only the owner may read it, it can hold the access token.
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o700);
+
+    let result = options.open(&script_path).and_then(|mut file| {
+        // The mode only applies to new files, a previous script may be readable by others.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o700))?;
+        }
+        file.write_all(export_script(command, access_token, include_token).as_bytes())
+    });
+
+    match result {
+        Ok(_) => Ok(script_path),
+        Err(err) => Err(format!(
+            "Unable to write {}: {}",
+            script_path.display(),
+            err
+        )),
+    }
+}
+
+fn quote_shell(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// The token is read from $ACCESS_TOKEN so that it never ends up in the script by mistake.
+fn quote_redacted(value: &str, access_token: &str) -> String {
+    if access_token.is_empty() {
+        return quote_shell(value);
+    }
+
+    value
+        .split(access_token)
+        .map(quote_shell)
+        .collect::<Vec<String>>()
+        .join("\"$ACCESS_TOKEN\"")
+}
+
+fn get_kept_variables() -> &'static [&'static str] {
+    if cfg!(windows) {
+        &WINDOWS_KEPT_VARIABLES
+    } else {
+        &UNIX_KEPT_VARIABLES
+    }
 }
 
 pub fn pre_launch(manifest: Main, mut tx: Sender<Message>) {
@@ -31,6 +268,7 @@ pub fn pre_launch(manifest: Main, mut tx: Sender<Message>) {
         None => {}
         Some(bin_folder) => {
             let mut i = 0;
+            let platform = Platform::current();
             for library in manifest.libraries.clone() {
                 let rules: Vec<Rule> = match library.rules {
                     None => {
@@ -40,7 +278,7 @@ pub fn pre_launch(manifest: Main, mut tx: Sender<Message>) {
                 };
                 i += 1;
 
-                match arguments::match_rules(rules, None) {
+                match arguments::match_rules(&rules, &HashSet::new(), &platform) {
                     RuleAction::Allow => {
                         let mut classiers: HashMap<String, LibraryDownloadArtifact> =
                             HashMap::new();
@@ -161,3 +399,60 @@ fn extract_natives(
 
     tx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_command(clear_env: bool) -> LaunchCommand {
+        let mut env = HashMap::new();
+        env.insert(String::from("MC_TOKEN"), Some(String::from("secret-token")));
+        env.insert(String::from("GAME_MODE"), Some(String::from("'fast'")));
+        env.insert(String::from("JAVA_TOOL_OPTIONS"), None);
+
+        LaunchCommand {
+            java_path: PathBuf::from("/usr/bin/java"),
+            game_dir: PathBuf::from("/home/steve/.minecraft"),
+            args: vec![
+                String::from("--accessToken"),
+                String::from("secret-token"),
+                String::from("--session=token:secret-token:uuid"),
+            ],
+            wrapper: vec![],
+            env,
+            clear_env,
+            hook_env: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn exported_scripts_hide_the_token() {
+        for clear_env in &[false, true] {
+            let script = export_script(&get_command(*clear_env), "secret-token", false);
+
+            assert!(!script.contains("secret-token"), "{}", script);
+            assert!(
+                script.contains("MC_TOKEN=''\"$ACCESS_TOKEN\"''"),
+                "{}",
+                script
+            );
+            assert!(
+                script.contains("'--session=token:'\"$ACCESS_TOKEN\"':uuid'"),
+                "{}",
+                script
+            );
+            assert!(script.contains("GAME_MODE=''\\''fast'\\'''"), "{}", script);
+            // The token has to be set before any variable uses it.
+            assert!(script.find("ACCESS_TOKEN=").unwrap() < script.find("MC_TOKEN=").unwrap());
+        }
+    }
+
+    #[test]
+    fn exported_scripts_can_include_the_token() {
+        let script = export_script(&get_command(false), "secret-token", true);
+
+        assert!(script.contains("ACCESS_TOKEN='secret-token'"));
+        assert!(script.contains("unset JAVA_TOOL_OPTIONS"));
+        assert_eq!(script.matches("secret-token").count(), 1);
+    }
+}
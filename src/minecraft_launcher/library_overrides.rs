@@ -0,0 +1,454 @@
+use crate::minecraft_launcher::arguments::Platform;
+use crate::minecraft_launcher::manifest::version::{Library, LibraryDownloadArtifact, Main};
+use crate::minecraft_launcher::path;
+
+use serde_derive::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SHIPPED_OVERRIDES: &str = include_str!("../../resources/library_overrides.json");
+const OVERRIDES_FILE: &str = "library_overrides.json";
+const MAVEN_CENTRAL: &str = "https://repo1.maven.org/maven2/";
+
+#[derive(Deserialize)]
+struct LibraryOverride {
+    // "group:artifact:version[:classifier]", "*" matches any part and the classifier can be
+    // left out.
+    library: String,
+    os: Option<String>,
+    arch: Option<String>,
+    // New coordinates, "*" keeps the part of the replaced library.
+    name: Option<String>,
+    // Native classifiers to replace, "natives-linux" by "natives-linux-arm64" for example.
+    #[serde(default)]
+    classifiers: HashMap<String, String>,
+    // Maven repository the replacements are downloaded from, Maven Central by default.
+    repository: Option<String>,
+}
+
+/// Replacements for libraries the official manifests don't provide for this platform, like
+/// the x86-only LWJGL 3 natives on ARM. LWJGL 2, used up to 1.12, has no ARM builds on Maven
+/// Central: an instance override can point its natives to a repository that has them.
+pub struct LibraryOverrides {
+    overrides: Vec<LibraryOverride>,
+}
+
+impl LibraryOverrides {
+    /// Loads the instance's overrides, then the shipped ones. Only the first override matching
+    /// a library is used, so an instance override without replacement keeps the original.
+    pub fn load(instance_dir: Option<&Path>) -> Result<LibraryOverrides, String> {
+        let mut overrides = vec![];
+
+        if let Some(instance_dir) = instance_dir {
+            let user_file = instance_dir.join(OVERRIDES_FILE);
+            if let Ok(body) = fs::read_to_string(&user_file) {
+                overrides.extend(parse_overrides(&body, &user_file.display().to_string())?);
+            }
+        }
+        overrides.extend(parse_overrides(
+            SHIPPED_OVERRIDES,
+            "shipped library overrides",
+        )?);
+
+        let platform = Platform::current();
+        overrides.retain(|library_override| {
+            library_override
+                .os
+                .as_ref()
+                .map_or(true, |os| *os == platform.name)
+                && library_override
+                    .arch
+                    .as_ref()
+                    .map_or(true, |arch| *arch == platform.arch)
+        });
+
+        Ok(LibraryOverrides { overrides })
+    }
+
+    /// Returns the version with its libraries replaced, before they are installed or put on
+    /// the classpath. The SHA-1 of the replacements comes from their repository, a replacement
+    /// that can't be verified is an error.
+    pub fn apply(&self, version: &Main) -> Result<Main, String> {
+        let mut version = version.clone();
+
+        for library in &mut version.libraries {
+            if let Some(library_override) = self
+                .overrides
+                .iter()
+                .find(|library_override| library_override.is_matching(&library.name))
+            {
+                *library = library_override.replace(library);
+                set_published_sha1(library)?;
+            }
+        }
+
+        Ok(version)
+    }
+}
+
+impl LibraryOverride {
+    fn is_matching(&self, name: &str) -> bool {
+        let name_parts: Vec<&str> = name.split(':').collect();
+        let pattern_parts: Vec<&str> = self.library.split(':').collect();
+
+        pattern_parts.len() <= name_parts.len()
+            && pattern_parts
+                .iter()
+                .zip(&name_parts)
+                .all(|(pattern, part)| *pattern == "*" || pattern == part)
+    }
+
+    fn replace(&self, library: &Library) -> Library {
+        let mut library = library.clone();
+        let repository = self.repository.as_deref().unwrap_or(MAVEN_CENTRAL);
+
+        let mut name_parts: Vec<String> = library.name.split(':').map(String::from).collect();
+        if let Some(name) = &self.name {
+            for (i, part) in name.split(':').enumerate() {
+                if part == "*" {
+                    continue;
+                }
+                match name_parts.get_mut(i) {
+                    None => name_parts.push(part.to_string()),
+                    Some(name_part) => *name_part = part.to_string(),
+                }
+            }
+        }
+        // Since 1.19 natives are libraries of their own, with the classifier in their name.
+        if let Some(classifier) = name_parts.get_mut(3) {
+            if let Some(replacement) = self.classifiers.get(classifier.as_str()) {
+                *classifier = replacement.clone();
+            }
+        }
+
+        let name = name_parts.join(":");
+        let renamed = name != library.name;
+        library.name = name;
+
+        if let Some(natives) = &mut library.natives {
+            for classifier in natives.values_mut() {
+                if let Some(replacement) = self.classifiers.get(classifier.as_str()) {
+                    *classifier = replacement.clone();
+                }
+            }
+        }
+
+        match &mut library.downloads {
+            None => {
+                // Downloaded from the maven layout of the library url, see install_libraries.
+                if renamed {
+                    library.url = Some(repository.trim_end_matches('/').to_string());
+                }
+            }
+            Some(downloads) => {
+                if renamed && downloads.artifact.is_some() {
+                    downloads.artifact = get_artifact(repository, &name_parts, None);
+                }
+
+                if let Some(classifiers) = downloads.classifiers.take() {
+                    downloads.classifiers = Some(
+                        classifiers
+                            .into_iter()
+                            .map(|(classifier, artifact)| {
+                                let replacement = self.classifiers.get(&classifier).cloned();
+                                if replacement.is_none() && !renamed {
+                                    return (classifier, artifact);
+                                }
+
+                                let classifier = replacement.unwrap_or(classifier);
+                                let artifact =
+                                    get_artifact(repository, &name_parts, Some(&classifier))
+                                        .unwrap_or(artifact);
+                                (classifier, artifact)
+                            })
+                            .collect(),
+                    );
+                }
+            }
+        }
+
+        library
+    }
+}
+
+fn parse_overrides(body: &str, origin: &str) -> Result<Vec<LibraryOverride>, String> {
+    match serde_json::from_str(body) {
+        Ok(overrides) => Ok(overrides),
+        Err(err) => Err(format!("Unable to read {}: {}", origin, err)),
+    }
+}
+
+// The replacements have no known size, install_libraries checks them against the SHA-1
+// published next to them instead.
+fn get_artifact(
+    repository: &str,
+    name_parts: &[String],
+    classifier: Option<&String>,
+) -> Option<LibraryDownloadArtifact> {
+    let group = name_parts.get(0)?.replace(".", "/");
+    let name = name_parts.get(1)?;
+    let version = name_parts.get(2)?;
+
+    let file_name = match classifier.or_else(|| name_parts.get(3)) {
+        None => format!("{}-{}", name, version),
+        Some(classifier) => format!("{}-{}-{}", name, version, classifier),
+    };
+    let path = format!("{}/{}/{}/{}.jar", group, name, version, file_name);
+
+    Some(LibraryDownloadArtifact {
+        url: format!("{}/{}", repository.trim_end_matches('/'), path),
+        path,
+        sha1: String::new(),
+        size: 0,
+    })
+}
+
+fn set_published_sha1(library: &mut Library) -> Result<(), String> {
+    if let Some(downloads) = &mut library.downloads {
+        let classifiers = downloads
+            .classifiers
+            .iter_mut()
+            .flat_map(|classifiers| classifiers.values_mut());
+        for artifact in downloads.artifact.iter_mut().chain(classifiers) {
+            if artifact.sha1.is_empty() {
+                artifact.sha1 = get_published_sha1(artifact)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Kept next to the library like in a local Maven repository, it is only downloaded once.
+fn get_published_sha1(artifact: &LibraryDownloadArtifact) -> Result<String, String> {
+    let cache_path = path::get_library_path(&artifact.path).map(|library_path| {
+        let mut cache_path = library_path.into_os_string();
+        cache_path.push(".sha1");
+        PathBuf::from(cache_path)
+    });
+
+    if let Some(sha1) = cache_path
+        .as_ref()
+        .and_then(|cache_path| fs::read_to_string(cache_path).ok())
+        .and_then(|body| parse_sha1(&body))
+    {
+        return Ok(sha1);
+    }
+
+    let url = format!("{}.sha1", artifact.url);
+    let sha1 = match parse_sha1(&path::read_file_from_url_to_string(&url)?) {
+        Some(sha1) => sha1,
+        None => return Err(format!("{} doesn't hold a SHA-1", url)),
+    };
+
+    if let Some(cache_path) = cache_path {
+        fs::write(cache_path, &sha1).unwrap_or(());
+    }
+
+    Ok(sha1)
+}
+
+// Maven repositories publish "<file>.sha1", sometimes followed by the file name.
+fn parse_sha1(body: &str) -> Option<String> {
+    let sha1 = body.split_whitespace().next()?.to_lowercase();
+
+    if sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(sha1)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library_override(json: &str) -> LibraryOverride {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn library(json: &str) -> Library {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn library_matching() {
+        let cases = [
+            ("org.lwjgl:*:3.2.1", "org.lwjgl:lwjgl:3.2.1", true),
+            ("org.lwjgl:*:3.2.1", "org.lwjgl:lwjgl-glfw:3.2.1", true),
+            // Without a classifier in the pattern, any classifier matches.
+            (
+                "org.lwjgl:*:3.2.1",
+                "org.lwjgl:lwjgl:3.2.1:natives-linux",
+                true,
+            ),
+            ("org.lwjgl:*:3.2.1", "org.lwjgl:lwjgl:3.2.2", false),
+            ("org.lwjgl:*:3.2.1", "org.lwjgl.lwjgl:lwjgl:3.2.1", false),
+            (
+                "org.lwjgl:*:3.3.1:natives-linux",
+                "org.lwjgl:lwjgl:3.3.1:natives-linux",
+                true,
+            ),
+            (
+                "org.lwjgl:*:3.3.1:natives-linux",
+                "org.lwjgl:lwjgl:3.3.1:natives-windows",
+                false,
+            ),
+            (
+                "org.lwjgl:*:3.3.1:natives-linux",
+                "org.lwjgl:lwjgl:3.3.1",
+                false,
+            ),
+            ("*:*:*", "net.java.jinput:jinput-platform:2.0.5", true),
+            // The shipped overrides, for every LWJGL 3 version but not LWJGL 2.
+            ("org.lwjgl:*:*", "org.lwjgl:lwjgl:3.1.6", true),
+            (
+                "org.lwjgl:*:*",
+                "org.lwjgl:lwjgl-glfw:3.3.3:natives-linux",
+                true,
+            ),
+            (
+                "org.lwjgl:*:*",
+                "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                false,
+            ),
+        ];
+
+        for (pattern, name, expected) in cases.iter() {
+            let library_override = library_override(&format!(r#"{{"library": "{}"}}"#, pattern));
+            assert_eq!(
+                library_override.is_matching(name),
+                *expected,
+                "{} with {}",
+                pattern,
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn library_replacement() {
+        let cases = [
+            // 1.19 and newer, the natives are libraries of their own.
+            (
+                r#"{"library": "org.lwjgl:*:3.3.1:natives-linux",
+                    "classifiers": {"natives-linux": "natives-linux-arm64"}}"#,
+                r#"{"name": "org.lwjgl:lwjgl:3.3.1:natives-linux",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar",
+                        "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux.jar",
+                        "sha1": "1de885aba434f934201b99f2f1afb142036ac189", "size": 110704}}}"#,
+                r#"{"name": "org.lwjgl:lwjgl:3.3.1:natives-linux-arm64",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux-arm64.jar",
+                        "url": "https://repo1.maven.org/maven2/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1-natives-linux-arm64.jar",
+                        "sha1": "", "size": 0}}}"#,
+            ),
+            (
+                r#"{"library": "org.lwjgl:*:*",
+                    "classifiers": {"natives-linux": "natives-linux-arm64"}}"#,
+                r#"{"name": "org.lwjgl:lwjgl-glfw:3.3.3:natives-linux",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3-natives-linux.jar",
+                        "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3-natives-linux.jar",
+                        "sha1": "a06ea8ed6de8a8fbe7c7c7c4e6de1d4bb3a0ed3b", "size": 107512}}}"#,
+                r#"{"name": "org.lwjgl:lwjgl-glfw:3.3.3:natives-linux-arm64",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3-natives-linux-arm64.jar",
+                        "url": "https://repo1.maven.org/maven2/org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3-natives-linux-arm64.jar",
+                        "sha1": "", "size": 0}}}"#,
+            ),
+            // The classpath part of a 1.19+ library has nothing to replace.
+            (
+                r#"{"library": "org.lwjgl:*:*",
+                    "classifiers": {"natives-linux": "natives-linux-arm64"}}"#,
+                r#"{"name": "org.lwjgl:lwjgl-glfw:3.3.3",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3.jar",
+                        "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3.jar",
+                        "sha1": "efa1eb78c5ccd840e9f329717109b5e892d72f8e", "size": 131840}}}"#,
+                r#"{"name": "org.lwjgl:lwjgl-glfw:3.3.3",
+                    "downloads": {"artifact": {
+                        "path": "org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3.jar",
+                        "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl-glfw/3.3.3/lwjgl-glfw-3.3.3.jar",
+                        "sha1": "efa1eb78c5ccd840e9f329717109b5e892d72f8e", "size": 131840}}}"#,
+            ),
+            // Older versions list the natives as classifiers of the library.
+            (
+                r#"{"library": "org.lwjgl:*:3.2.2",
+                    "classifiers": {"natives-linux": "natives-linux-arm64"}}"#,
+                r#"{"name": "org.lwjgl:lwjgl:3.2.2",
+                    "natives": {"linux": "natives-linux", "windows": "natives-windows"},
+                    "downloads": {
+                        "artifact": {
+                            "path": "org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2.jar",
+                            "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2.jar",
+                            "sha1": "8ad6294407e15780b43e84929c40e4c5e997972e", "size": 321900},
+                        "classifiers": {"natives-linux": {
+                            "path": "org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux.jar",
+                            "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux.jar",
+                            "sha1": "ae7976827ca2a3741f6b9a843a89bacd637af350", "size": 124776}}}}"#,
+                r#"{"name": "org.lwjgl:lwjgl:3.2.2",
+                    "natives": {"linux": "natives-linux-arm64", "windows": "natives-windows"},
+                    "downloads": {
+                        "artifact": {
+                            "path": "org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2.jar",
+                            "url": "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2.jar",
+                            "sha1": "8ad6294407e15780b43e84929c40e4c5e997972e", "size": 321900},
+                        "classifiers": {"natives-linux-arm64": {
+                            "path": "org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux-arm64.jar",
+                            "url": "https://repo1.maven.org/maven2/org/lwjgl/lwjgl/3.2.2/lwjgl-3.2.2-natives-linux-arm64.jar",
+                            "sha1": "", "size": 0}}}}"#,
+            ),
+            // Libraries without downloads come from the maven layout of their url.
+            (
+                r#"{"library": "net.java.jinput:jinput-platform:2.0.5", "name": "*:*:2.0.7",
+                    "repository": "https://maven.example.org/releases/"}"#,
+                r#"{"name": "net.java.jinput:jinput-platform:2.0.5",
+                    "url": "https://libraries.minecraft.net/"}"#,
+                r#"{"name": "net.java.jinput:jinput-platform:2.0.7",
+                    "url": "https://maven.example.org/releases"}"#,
+            ),
+            // Nothing to replace keeps the library as it is.
+            (
+                r#"{"library": "org.lwjgl:*:3.2.2",
+                    "classifiers": {"natives-linux": "natives-linux-arm64"}}"#,
+                r#"{"name": "org.lwjgl:lwjgl-jemalloc:3.2.2",
+                    "url": "https://libraries.minecraft.net/"}"#,
+                r#"{"name": "org.lwjgl:lwjgl-jemalloc:3.2.2",
+                    "url": "https://libraries.minecraft.net/"}"#,
+            ),
+        ];
+
+        for (i, (override_json, library_json, expected_json)) in cases.iter().enumerate() {
+            let replaced = library_override(override_json).replace(&library(library_json));
+            assert_eq!(
+                serde_json::to_value(&replaced).unwrap(),
+                serde_json::to_value(&library(expected_json)).unwrap(),
+                "case {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn published_sha1() {
+        let cases = [
+            (
+                "1de885aba434f934201b99f2f1afb142036ac189",
+                Some("1de885aba434f934201b99f2f1afb142036ac189"),
+            ),
+            (
+                "1DE885ABA434F934201B99F2F1AFB142036AC189  lwjgl-3.3.1-natives-linux.jar\n",
+                Some("1de885aba434f934201b99f2f1afb142036ac189"),
+            ),
+            ("<html><body>404 Not Found</body></html>", None),
+            ("", None),
+        ];
+
+        for (i, (body, expected)) in cases.iter().enumerate() {
+            assert_eq!(parse_sha1(body).as_deref(), *expected, "case {}", i);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::path;
+
+use std::fs;
+use std::path::Path;
+
+const LOG4J_CORE: &str = "org.apache.logging.log4j:log4j-core:";
+
+// Message lookups were removed in 2.16.0.
+const FIXED_VERSION: (u32, u32, u32) = (2, 16, 0);
+// First versions knowing "%msg{nolookups}" and "log4j2.formatMsgNoLookups".
+const NO_LOOKUPS_PATTERN_VERSION: (u32, u32, u32) = (2, 7, 0);
+const NO_LOOKUPS_FLAG_VERSION: (u32, u32, u32) = (2, 10, 0);
+
+// Same as the configs Mojang published for 1.7-1.11.2 and 1.12-1.16.5.
+const LEGACY_CONFIG: (&str, &str) = (
+    "log4j2_17-111.xml",
+    include_str!("../../resources/log4j2_17-111.xml"),
+);
+const NO_LOOKUPS_CONFIG: (&str, &str) = (
+    "log4j2_112-116.xml",
+    include_str!("../../resources/log4j2_112-116.xml"),
+);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mitigation {
+    // Drops every message containing "${", older log4j has no way to turn lookups off.
+    LegacyConfig,
+    NoLookupsConfig,
+    NoLookupsFlag,
+}
+
+impl Mitigation {
+    pub fn replaces_config(&self) -> bool {
+        *self != Mitigation::NoLookupsFlag
+    }
+
+    /// Writes the patched config if needed, the arguments replace the manifest's logging one.
+    pub fn get_jvm_args(&self) -> Result<Vec<String>, String> {
+        let (file_name, body) = match self {
+            Mitigation::NoLookupsFlag => {
+                return Ok(vec!["-Dlog4j2.formatMsgNoLookups=true".to_string()])
+            }
+            Mitigation::LegacyConfig => LEGACY_CONFIG,
+            Mitigation::NoLookupsConfig => NO_LOOKUPS_CONFIG,
+        };
+
+        let config_path = match path::get_assets_folder("log_configs") {
+            None => return Err("Unable to get log_configs folder".to_string()),
+            Some(log_folder) => log_folder.join(format!("rusty-craft-{}", file_name)),
+        };
+        write_if_changed(&config_path, body)?;
+
+        Ok(vec![format!(
+            "-Dlog4j.configurationFile={}",
+            config_path.display()
+        )])
+    }
+
+    pub fn get_notice(&self, log4j_version: &str) -> String {
+        match self {
+            Mitigation::NoLookupsFlag => format!(
+                "log4j {} is vulnerable to Log4Shell, message lookups were turned off",
+                log4j_version
+            ),
+            _ => format!(
+                "log4j {} is vulnerable to Log4Shell, a patched log config is used",
+                log4j_version
+            ),
+        }
+    }
+}
+
+/// The log4j-core version of the resolved libraries and how to protect it, if it is vulnerable.
+pub fn get_mitigation(version: &version::Main) -> Option<(String, Mitigation)> {
+    let log4j_version = version
+        .libraries
+        .iter()
+        .find_map(|library| library.name.strip_prefix(LOG4J_CORE))?
+        .split(':')
+        .next()?
+        .to_string();
+
+    let parsed = parse_version(&log4j_version)?;
+    let mitigation = if parsed >= FIXED_VERSION {
+        return None;
+    } else if parsed >= NO_LOOKUPS_FLAG_VERSION {
+        Mitigation::NoLookupsFlag
+    } else if parsed >= NO_LOOKUPS_PATTERN_VERSION {
+        Mitigation::NoLookupsConfig
+    } else {
+        Mitigation::LegacyConfig
+    };
+
+    Some((log4j_version, mitigation))
+}
+
+// "2.0-beta9" is read as 2.0.0, pre-releases are older than any release anyway.
+fn parse_version(log4j_version: &str) -> Option<(u32, u32, u32)> {
+    let release = log4j_version.split('-').next()?;
+    let mut parts = release.split('.').map(|part| part.parse::<u32>().ok());
+
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+
+    Some((major, minor, patch))
+}
+
+fn write_if_changed(config_path: &Path, body: &str) -> Result<(), String> {
+    if fs::read_to_string(config_path).map_or(false, |current| current == body) {
+        return Ok(());
+    }
+
+    fs::write(config_path, body).map_err(|err| {
+        format!(
+            "Unable to write log config {}: {}",
+            config_path.display(),
+            err
+        )
+    })
+}
@@ -1,5 +1,6 @@
 pub mod assets;
 pub mod java;
 pub mod java_versions;
+pub mod launcher_profiles;
 pub mod main;
 pub mod version;
@@ -1,55 +1,31 @@
+use crate::minecraft_launcher::path;
 use chrono::{DateTime, Utc};
 use serde_derive::Deserialize;
-use std::env::consts;
+use std::collections::HashMap;
 
+// Platforms ("linux", "mac-os-arm64", "windows-x64"...) and components ("jre-legacy",
+// "java-runtime-gamma"...) are keys so that new ones are picked up without code changes.
 #[derive(Deserialize, Clone)]
 pub struct Main {
-    pub linux: OsVersions,
-    #[serde(rename = "linux-i386")]
-    pub linux_i386: OsVersions,
-    #[serde(rename = "mac-os")]
-    pub mac_os: OsVersions,
-    #[serde(rename = "windows-x64")]
-    pub windows_x64: OsVersions,
-    #[serde(rename = "windows-x86")]
-    pub windows_x86: OsVersions,
+    #[serde(flatten)]
+    pub platforms: HashMap<String, OsVersions>,
 }
 
 impl Main {
     pub fn get_os_version(self) -> Option<OsVersions> {
-        match consts::OS {
-            "windows" => match consts::ARCH {
-                "x86" => Some(self.windows_x86),    //"windows-x86",
-                "x86_64" => Some(self.windows_x64), //"windows-x64",
-                &_ => None,
-            },
-            "macos" => Some(self.mac_os), //"mac-os",
-            &_ => match consts::ARCH {
-                "x86" => Some(self.linux_i386), //"linux-i386",
-                &_ => Some(self.linux),         //"linux"
-            },
-        }
+        self.platforms.get(path::get_os_java_name()).cloned()
     }
 }
 
 #[derive(Deserialize, Clone)]
 pub struct OsVersions {
-    #[serde(rename = "java-runtime-alpha")]
-    pub java_runtime_alpha: Vec<Version>,
-    #[serde(rename = "jre-legacy")]
-    pub jre_legacy: Vec<Version>,
-    #[serde(rename = "minecraft-java-exe")]
-    pub java_exe: Vec<Version>,
+    #[serde(flatten)]
+    pub components: HashMap<String, Vec<Version>>,
 }
 
 impl OsVersions {
     pub fn get_java_version(self, version: &str) -> Option<Vec<Version>> {
-        match version {
-            "java-runtime-alpha" => Some(self.java_runtime_alpha),
-            "jre-legacy" => Some(self.jre_legacy),
-            "minecraft-java-exe" => Some(self.java_exe),
-            &_ => None,
-        }
+        self.components.get(version).cloned()
     }
 }
 
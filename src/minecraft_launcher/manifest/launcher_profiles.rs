@@ -0,0 +1,102 @@
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Keys we don't know about are kept in `other` so that installers (Forge, OptiFine...)
+// and the official launcher don't lose their data when we rewrite the file.
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Main {
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    #[serde(flatten)]
+    pub other: HashMap<String, Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub _type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(rename = "lastUsed", skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<String>,
+    #[serde(rename = "lastVersionId", skip_serializing_if = "Option::is_none")]
+    pub last_version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(rename = "gameDir", skip_serializing_if = "Option::is_none")]
+    pub game_dir: Option<String>,
+    #[serde(rename = "javaDir", skip_serializing_if = "Option::is_none")]
+    pub java_dir: Option<String>,
+    #[serde(rename = "javaArgs", skip_serializing_if = "Option::is_none")]
+    pub java_args: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<Resolution>,
+    #[serde(rename = "rustyCraft", skip_serializing_if = "Option::is_none")]
+    pub rusty_craft: Option<ProfileSettings>,
+    #[serde(flatten)]
+    pub other: HashMap<String, Value>,
+}
+
+// Settings the official launcher doesn't have, kept under their own key.
+#[derive(Deserialize, Serialize, Clone, Default)]
+pub struct ProfileSettings {
+    #[serde(rename = "minMemory", skip_serializing_if = "Option::is_none")]
+    pub min_memory: Option<String>,
+    #[serde(rename = "maxMemory", skip_serializing_if = "Option::is_none")]
+    pub max_memory: Option<String>,
+    #[serde(rename = "gameArgs", skip_serializing_if = "Option::is_none")]
+    pub game_args: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fullscreen: Option<bool>,
+    // Only one of them is used, the world first, then the server and the realm.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub world: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub realm: Option<String>,
+    // Command the java command line is appended to, "gamemoderun" or "prime-run" for example.
+    #[serde(rename = "wrapperCommand", skip_serializing_if = "Option::is_none")]
+    pub wrapper_command: Option<String>,
+    #[serde(rename = "preLaunchCommand", skip_serializing_if = "Option::is_none")]
+    pub pre_launch_command: Option<String>,
+    #[serde(rename = "postExitCommand", skip_serializing_if = "Option::is_none")]
+    pub post_exit_command: Option<String>,
+    // JDWP port, the debugger attaches there.
+    #[serde(rename = "debugPort", skip_serializing_if = "Option::is_none")]
+    pub debug_port: Option<u16>,
+    #[serde(rename = "debugSuspend", skip_serializing_if = "Option::is_none")]
+    pub debug_suspend: Option<bool>,
+    #[serde(rename = "flightRecorder", skip_serializing_if = "Option::is_none")]
+    pub flight_recorder: Option<bool>,
+    // Agent jars, each with its own options: "agent.jar=opts other.jar".
+    #[serde(rename = "javaAgents", skip_serializing_if = "Option::is_none")]
+    pub java_agents: Option<String>,
+    // Given to the game only, a null value removes an inherited variable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<HashMap<String, Option<String>>>,
+    #[serde(rename = "clearEnvironment", skip_serializing_if = "Option::is_none")]
+    pub clear_environment: Option<bool>,
+    // Name of the instance owning this profile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(flatten)]
+    pub other: HashMap<String, Value>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+pub fn parse_launcher_profiles(profiles_str: &str) -> serde_json::Result<Main> {
+    serde_json::from_str(profiles_str)
+}
+
+pub fn serialize_launcher_profiles(profiles: &Main) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(profiles)
+}
@@ -83,7 +83,10 @@ impl Main {
         }
 
         for i in from.libraries {
-            self.libraries.push(i);
+            // The manifest may already have been merged with its parent when it was installed.
+            if !self.libraries.iter().any(|library| library.name == i.name) {
+                self.libraries.push(i);
+            }
         }
 
         if self.logging.is_none() && from.logging.is_some() {
@@ -331,6 +334,13 @@ impl Main {
         }
     }
 
+    pub fn get_java_major_version(&self) -> u8 {
+        match &self.java_version {
+            None => 8,
+            Some(java_version) => java_version.major_version,
+        }
+    }
+
     pub fn is_modded(&self) -> bool {
         let id = self.id.to_lowercase();
         id.contains("fabric")
@@ -101,6 +101,17 @@ impl ModLoaderHandler {
         }
     }
 
+    pub fn get_loader_by_name(&self, name: &str) -> Option<Box<dyn ModLoaderInstaller>> {
+        if self.vanilla.get_name() == name {
+            return Some(self.vanilla.clone_instance());
+        }
+
+        self.mod_loaders
+            .iter()
+            .find(|mod_loader| mod_loader.get_name() == name)
+            .map(|mod_loader| mod_loader.clone_instance())
+    }
+
     pub fn get_loaders_for_version(
         &self,
         version: String,
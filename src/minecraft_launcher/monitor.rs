@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+
+// Ten minutes of history at one sample per second.
+const HISTORY_CAPACITY: usize = 600;
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+// USER_HZ, the unit of /proc times, is 100 on every Linux the game runs on.
+const CLOCK_TICKS: f64 = 100.0;
+
+#[derive(Clone, Copy)]
+pub struct ProcessSample {
+    // Of one core, a game using two full cores is at 200%.
+    pub cpu_percent: f64,
+    pub resident_bytes: u64,
+    pub threads: u64,
+}
+
+/// Reads the resources of a running process from `/proc/<pid>`, only Linux has it.
+pub struct ProcessMonitor {
+    pid: u32,
+    last_cpu_ticks: Option<(u64, Instant)>,
+    last_sample: Option<Instant>,
+    pub latest: Option<ProcessSample>,
+    // Resident memory of each sample, in bytes.
+    pub memory_history: VecDeque<u64>,
+}
+
+impl ProcessMonitor {
+    pub fn new(pid: u32) -> ProcessMonitor {
+        ProcessMonitor {
+            pid,
+            last_cpu_ticks: None,
+            last_sample: None,
+            latest: None,
+            memory_history: VecDeque::new(),
+        }
+    }
+
+    pub fn get_peak_memory(&self) -> u64 {
+        self.memory_history.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Takes a new sample once per interval, called on every tick.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        if let Some(last_sample) = self.last_sample {
+            if now - last_sample < SAMPLE_INTERVAL {
+                return;
+            }
+        }
+        self.last_sample = Some(now);
+
+        let (cpu_ticks, status) = match (self.read_cpu_ticks(), self.read_status()) {
+            (Some(cpu_ticks), Some(status)) => (cpu_ticks, status),
+            _ => {
+                self.latest = None;
+                return;
+            }
+        };
+
+        // The first sample has nothing to compare with.
+        let cpu_percent = match self.last_cpu_ticks {
+            Some((last_ticks, last_time)) if now > last_time => {
+                let used = cpu_ticks.saturating_sub(last_ticks) as f64 / CLOCK_TICKS;
+                used / (now - last_time).as_secs_f64() * 100.0
+            }
+            _ => 0.0,
+        };
+        self.last_cpu_ticks = Some((cpu_ticks, now));
+
+        let (resident_bytes, threads) = status;
+        self.latest = Some(ProcessSample {
+            cpu_percent,
+            resident_bytes,
+            threads,
+        });
+
+        if self.memory_history.len() >= HISTORY_CAPACITY {
+            self.memory_history.pop_front();
+        }
+        self.memory_history.push_back(resident_bytes);
+    }
+
+    // utime and stime are the 14th and 15th fields, the name before them may contain spaces.
+    fn read_cpu_ticks(&self) -> Option<u64> {
+        let stat = fs::read_to_string(format!("/proc/{}/stat", self.pid)).ok()?;
+        let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+
+        let utime = fields.get(11)?.parse::<u64>().ok()?;
+        let stime = fields.get(12)?.parse::<u64>().ok()?;
+
+        Some(utime + stime)
+    }
+
+    fn read_status(&self) -> Option<(u64, u64)> {
+        let status = fs::read_to_string(format!("/proc/{}/status", self.pid)).ok()?;
+
+        let mut resident = None;
+        let mut threads = None;
+        for line in status.lines() {
+            if let Some(value) = line.strip_prefix("VmRSS:") {
+                resident = value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .map(|kilobytes| kilobytes * 1024);
+            } else if let Some(value) = line.strip_prefix("Threads:") {
+                threads = value.trim().parse::<u64>().ok();
+            }
+        }
+
+        Some((resident?, threads?))
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    let megabytes = bytes / (1024 * 1024);
+    match megabytes {
+        0..=1023 => format!("{} MiB", megabytes),
+        _ => format!("{:.1} GiB", megabytes as f64 / 1024.0),
+    }
+}
@@ -1,4 +1,6 @@
+use crate::minecraft_launcher::config;
 use crate::minecraft_launcher::install::java;
+use crate::minecraft_launcher::jvm;
 use crate::minecraft_launcher::manifest::version;
 
 use directories::BaseDirs;
@@ -262,32 +264,69 @@ pub fn get_bin_folder(version_name: String) -> Option<PathBuf> {
     )
 }
 
-fn get_os_java_name() -> &'static str {
+pub fn get_os_java_name() -> &'static str {
     match consts::OS {
         "windows" => match consts::ARCH {
             "x86" => "windows-x86",
             "x86_64" => "windows-x64",
+            "aarch64" => "windows-arm64",
             &_ => "",
         },
-        "macos" => "mac-os",
+        "macos" => match consts::ARCH {
+            "aarch64" => "mac-os-arm64",
+            &_ => "mac-os",
+        },
         &_ => match consts::ARCH {
             "x86" => "linux-i386",
+            "aarch64" => "linux-arm64",
             &_ => "linux",
         },
     }
 }
 
 pub fn get_java_executable_path(version_manifest: &version::Main) -> Result<PathBuf, &str> {
-    match get_java_folder_path_sub(
+    let managed_java = match get_java_folder_path_sub(
         &(match version_manifest.java_version.clone() {
             None => String::from("jre-legacy"),
             Some(v) => v.component,
         }),
     ) {
-        None => Err("Unable to get java folder!"),
-        Some(java_folder) => Ok(java_folder
-            .join(java::get_java_folder_for_os())
-            .join(java::get_java_ex_for_os())),
+        None => None,
+        Some(java_folder) => Some(
+            java_folder
+                .join(java::get_java_folder_for_os())
+                .join(java::get_java_ex_for_os()),
+        ),
+    };
+
+    let prefer_system_java = config::read_config().unwrap_or_default().prefer_system_java;
+    if let Some(managed_java) = &managed_java {
+        if managed_java.exists() && !prefer_system_java {
+            return Ok(managed_java.clone());
+        }
+    }
+
+    match jvm::find_jvm_for_version(version_manifest.get_java_major_version()) {
+        Ok(system_jvm) => Ok(system_jvm.executable),
+        Err(_) => match managed_java {
+            Some(managed_java) if managed_java.exists() => Ok(managed_java),
+            _ => Err("Unable to find a Java runtime for this version!"),
+        },
+    }
+}
+
+pub fn get_launcher_profiles_path() -> PathBuf {
+    get_minecraft_directory().join("launcher_profiles.json")
+}
+
+pub fn get_launcher_folder() -> Option<PathBuf> {
+    get_minecraft_sub_folder("rusty-craft")
+}
+
+pub fn get_launcher_config_path() -> PathBuf {
+    match get_launcher_folder() {
+        None => get_minecraft_directory().join("rusty-craft.json"),
+        Some(launcher_folder) => launcher_folder.join("config.json"),
     }
 }
 
@@ -0,0 +1,185 @@
+use crate::minecraft_launcher::instance::Instance;
+use crate::minecraft_launcher::manifest::launcher_profiles;
+use crate::minecraft_launcher::manifest::launcher_profiles::{Profile, ProfileSettings};
+use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::path;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Read, Write};
+
+pub fn read_launcher_profiles() -> Result<launcher_profiles::Main, String> {
+    let profiles_path = path::get_launcher_profiles_path();
+
+    if !profiles_path.exists() {
+        let mut profiles = launcher_profiles::Main::default();
+        profiles.other.insert("version".to_string(), Value::from(3));
+        return Ok(profiles);
+    }
+
+    let mut file = match File::open(&profiles_path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Unable to open launcher_profiles.json: {}", err)),
+    };
+
+    let mut body = String::new();
+    match file.read_to_string(&mut body) {
+        Ok(_) => match launcher_profiles::parse_launcher_profiles(&body) {
+            Ok(profiles) => Ok(profiles),
+            Err(err) => Err(format!("launcher_profiles.json wrongly formatted! {}", err)),
+        },
+        Err(err) => Err(format!("Unable to read launcher_profiles.json: {}", err)),
+    }
+}
+
+pub fn write_launcher_profiles(profiles: &launcher_profiles::Main) -> Result<(), String> {
+    match launcher_profiles::serialize_launcher_profiles(profiles) {
+        Ok(body) => match File::create(path::get_launcher_profiles_path()) {
+            Ok(mut file) => match file.write_all(body.as_bytes()) {
+                Ok(_) => Ok(()),
+                Err(err) => Err(format!("Unable to write launcher_profiles.json: {}", err)),
+            },
+            Err(err) => Err(format!("Unable to create launcher_profiles.json: {}", err)),
+        },
+        Err(err) => Err(format!("Unable to serialize launcher profiles: {}", err)),
+    }
+}
+
+/// Makes sure every installed version has a profile, creating the file if needed.
+pub fn update_launcher_profiles(installed: &[version::Main]) -> Result<(), String> {
+    // Never overwrite a file we couldn't understand, other launchers may rely on it.
+    let mut profiles = read_launcher_profiles()?;
+
+    let mut added = false;
+    for version in installed {
+        added |= add_profile_if_missing(&mut profiles, &version.id);
+    }
+
+    // Other launchers watch the file, it is only written when something changed.
+    if added {
+        write_launcher_profiles(&profiles)
+    } else {
+        Ok(())
+    }
+}
+
+pub fn add_version_profile(version_id: &str) -> Result<(), String> {
+    let mut profiles = read_launcher_profiles()?;
+
+    if add_profile_if_missing(&mut profiles, version_id) {
+        write_launcher_profiles(&profiles)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the id and content of the profile used to launch `version_id`, profile ids are
+/// accepted too.
+pub fn get_version_profile(version_id: &str) -> (String, Profile) {
+    if let Ok(profiles) = read_launcher_profiles() {
+        if let Some(profile_id) = find_profile_id(&profiles, version_id) {
+            if let Some(profile) = profiles.profiles.get(&profile_id) {
+                return (profile_id, profile.clone());
+            }
+        }
+    }
+
+    (version_id.to_string(), new_profile(version_id))
+}
+
+pub fn save_profile(profile_id: &str, profile: Profile) -> Result<(), String> {
+    let mut profiles = read_launcher_profiles()?;
+
+    profiles.profiles.insert(profile_id.to_string(), profile);
+
+    write_launcher_profiles(&profiles)
+}
+
+/// Creates or updates the profile of an instance, keeping the settings it already has.
+pub fn add_instance_profile(instance: &Instance) -> Result<(), String> {
+    let mut profiles = read_launcher_profiles()?;
+
+    let profile = profiles
+        .profiles
+        .entry(instance.profile_id.clone())
+        .or_insert_with(|| new_profile(&instance.version_id));
+
+    profile.name = Some(instance.name.clone());
+    profile.last_version_id = Some(instance.version_id.clone());
+    profile.game_dir = Some(instance.get_game_dir().display().to_string());
+    profile
+        .rusty_craft
+        .get_or_insert_with(ProfileSettings::default)
+        .instance = Some(instance.name.clone());
+
+    write_launcher_profiles(&profiles)
+}
+
+pub fn remove_profile(profile_id: &str) -> Result<(), String> {
+    let mut profiles = read_launcher_profiles()?;
+
+    match profiles.profiles.remove(profile_id) {
+        None => Ok(()),
+        Some(_) => write_launcher_profiles(&profiles),
+    }
+}
+
+pub fn touch_profile(profile_id: &str) -> Result<(), String> {
+    let mut profiles = read_launcher_profiles()?;
+
+    match profiles.profiles.get_mut(profile_id) {
+        None => Ok(()),
+        Some(profile) => {
+            profile.last_used = Some(get_timestamp());
+            write_launcher_profiles(&profiles)
+        }
+    }
+}
+
+fn find_profile_id(profiles: &launcher_profiles::Main, version_id: &str) -> Option<String> {
+    if profiles.profiles.contains_key(version_id) {
+        return Some(version_id.to_string());
+    }
+
+    profiles
+        .profiles
+        .iter()
+        .find(|(_, profile)| {
+            profile.last_version_id.as_deref() == Some(version_id) && !is_instance_profile(profile)
+        })
+        .map(|(id, _)| id.clone())
+}
+
+fn is_instance_profile(profile: &Profile) -> bool {
+    profile
+        .rusty_craft
+        .as_ref()
+        .map_or(false, |settings| settings.instance.is_some())
+}
+
+fn add_profile_if_missing(profiles: &mut launcher_profiles::Main, version_id: &str) -> bool {
+    let known = find_profile_id(profiles, version_id).is_some();
+
+    if !known {
+        profiles
+            .profiles
+            .insert(version_id.to_string(), new_profile(version_id));
+    }
+
+    !known
+}
+
+fn new_profile(version_id: &str) -> Profile {
+    Profile {
+        name: Some(version_id.to_string()),
+        _type: Some("custom".to_string()),
+        created: Some(get_timestamp()),
+        last_version_id: Some(version_id.to_string()),
+        ..Profile::default()
+    }
+}
+
+fn get_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
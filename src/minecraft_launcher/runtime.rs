@@ -0,0 +1,463 @@
+use crate::minecraft_launcher::app::download_tab::Message;
+use crate::minecraft_launcher::install::java;
+use crate::minecraft_launcher::jvm::list_sub_folders;
+use crate::minecraft_launcher::manifest;
+use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::path;
+
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+const USAGE: &str = "Usage: rusty-craft runtimes [list | verify [component] | reinstall <component> | remove <component> | prune]";
+
+#[derive(Clone)]
+pub struct InstalledRuntime {
+    pub component: String,
+    pub os: String,
+    pub version: Option<String>,
+    pub size: u64,
+    pub path: PathBuf,
+    pub used_by: Vec<String>,
+    pub major_version: Option<u8>,
+}
+
+impl InstalledRuntime {
+    pub fn get_java_folder(&self) -> PathBuf {
+        self.path.join(&self.component)
+    }
+
+    pub fn is_used(&self) -> bool {
+        !self.used_by.is_empty()
+    }
+}
+
+/// Lists runtimes found in `runtime/<component>/<os>` along with the installed versions
+/// needing them.
+pub fn list_installed_runtimes(installed: &[version::Main]) -> Vec<InstalledRuntime> {
+    let mut runtimes: Vec<InstalledRuntime> = Vec::new();
+
+    let runtime_folder = match path::get_minecraft_sub_folder("runtime") {
+        None => return runtimes,
+        Some(runtime_folder) => runtime_folder,
+    };
+
+    for component_folder in list_sub_folders(&runtime_folder) {
+        let component = get_file_name(&component_folder);
+
+        for os_folder in list_sub_folders(&component_folder) {
+            // Folders are created before anything is downloaded into them.
+            if !os_folder.join(&component).exists() {
+                continue;
+            }
+
+            let mut used_by = Vec::new();
+            let mut major_version = None;
+
+            if get_file_name(&os_folder) == path::get_os_java_name() {
+                for version in installed {
+                    let java_version = get_java_version(version, installed);
+                    if java_version.0 == component {
+                        used_by.push(version.id.clone());
+                        major_version = Some(java_version.1);
+                    }
+                }
+            }
+
+            runtimes.push(InstalledRuntime {
+                component: component.clone(),
+                os: get_file_name(&os_folder),
+                version: read_version_marker(&os_folder),
+                size: get_folder_size(&os_folder),
+                path: os_folder,
+                used_by,
+                major_version,
+            });
+        }
+    }
+
+    runtimes.sort_by(|a, b| a.component.cmp(&b.component).then(a.os.cmp(&b.os)));
+
+    runtimes
+}
+
+/// Checks the runtime files against Mojang's component manifest, returns a list of problems.
+pub fn verify_runtime(runtime: &InstalledRuntime) -> Result<Vec<String>, String> {
+    let java_manifest = java::get_java_version_manifest()?;
+
+    let online_version = match java_manifest
+        .platforms
+        .get(&runtime.os)
+        .and_then(|os_version| os_version.components.get(&runtime.component))
+        .and_then(|versions| versions.get(0))
+    {
+        None => {
+            return Err(format!(
+                "Mojang doesn't provide {} for {}, it can't be verified",
+                runtime.component, runtime.os
+            ))
+        }
+        Some(online_version) => online_version.clone(),
+    };
+
+    let mut problems: Vec<String> = Vec::new();
+
+    match &runtime.version {
+        None => problems.push("Missing .version file".to_string()),
+        Some(installed_version) => {
+            if installed_version != &online_version.version.name {
+                problems.push(format!(
+                    "Installed version {} doesn't match {}",
+                    installed_version, online_version.version.name
+                ));
+            }
+        }
+    }
+
+    let body = path::read_file_from_url_to_string(&online_version.manifest.url)?;
+    let component_manifest = match manifest::java::parse_java_version_manifest(&body) {
+        Ok(component_manifest) => component_manifest,
+        Err(err) => return Err(format!("Failed to parse java_version_manifest {}", err)),
+    };
+
+    let java_folder = runtime.get_java_folder();
+
+    for (file_path, element) in component_manifest.files {
+        let element_path = java_folder.join(&file_path);
+
+        match element.element_type.as_str() {
+            "directory" => {
+                if !element_path.is_dir() {
+                    problems.push(format!("Missing folder {}", file_path));
+                }
+            }
+            "file" => match element_path.symlink_metadata() {
+                Err(_) => problems.push(format!("Missing file {}", file_path)),
+                Ok(meta) => {
+                    if let Some(downloads) = element.downloads {
+                        if meta.len() != downloads.raw.size {
+                            problems.push(format!("Wrong size for {}", file_path));
+                        }
+                    }
+                    if element.executable && !is_executable(&meta) {
+                        problems.push(format!("{} isn't executable", file_path));
+                    }
+                }
+            },
+            "link" => match fs::read_link(&element_path) {
+                Err(_) => problems.push(format!("Missing link {}", file_path)),
+                Ok(link_target) => {
+                    let link_folder = element_path.parent().unwrap_or(&java_folder);
+                    let expected = normalize(&link_folder.join(element.target.unwrap_or_default()));
+                    if normalize(&link_folder.join(link_target)) != expected {
+                        problems.push(format!("Link {} has the wrong target", file_path));
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Installs the runtime again from the provider serving this platform, the previous files are
+/// only replaced once the new ones are all there.
+pub fn reinstall_runtime(
+    runtime: &InstalledRuntime,
+    tx: Sender<Message>,
+) -> Option<Sender<Message>> {
+    if runtime.os != path::get_os_java_name() {
+        tx.send(Message::Error(format!(
+            "{} can only be reinstalled from a {} machine",
+            runtime.component, runtime.os
+        )))
+        .unwrap_or(());
+        return None;
+    }
+
+    let major_version = match runtime
+        .major_version
+        .or_else(|| get_component_major_version(&runtime.component))
+    {
+        Some(major_version) => major_version,
+        None => {
+            tx.send(Message::Error(format!(
+                "No installed version tells which Java {} is, it can't be reinstalled",
+                runtime.component
+            )))
+            .unwrap_or(());
+            return None;
+        }
+    };
+
+    let new_path = runtime.path.with_file_name(format!("{}.new", runtime.os));
+    if new_path.exists() {
+        fs::remove_dir_all(&new_path).unwrap_or(());
+    }
+    if let Err(err) = fs::create_dir_all(&new_path) {
+        tx.send(Message::Error(format!(
+            "Unable to create {}: {}",
+            new_path.display(),
+            err
+        )))
+        .unwrap_or(());
+        return None;
+    }
+
+    let tx = match java::install_runtime(&runtime.component, major_version, new_path.clone(), tx) {
+        Some(tx) => tx,
+        None => {
+            fs::remove_dir_all(&new_path).unwrap_or(());
+            return None;
+        }
+    };
+
+    match java::replace_folder(&new_path, &runtime.path) {
+        Ok(_) => Some(tx),
+        Err(err) => {
+            tx.send(Message::Error(err)).unwrap_or(());
+            fs::remove_dir_all(&new_path).unwrap_or(());
+            None
+        }
+    }
+}
+
+pub fn remove_runtime(runtime: &InstalledRuntime) -> Result<(), String> {
+    if runtime.is_used() {
+        return Err(format!(
+            "{} is still used by {}",
+            runtime.component,
+            runtime.used_by.join(", ")
+        ));
+    }
+
+    match fs::remove_dir_all(&runtime.path) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!(
+            "Unable to remove {}: {}",
+            runtime.path.display(),
+            err
+        )),
+    }
+}
+
+pub fn remove_unused_runtimes(installed: &[version::Main]) -> Vec<Result<String, String>> {
+    list_installed_runtimes(installed)
+        .iter()
+        .filter(|runtime| !runtime.is_used())
+        .map(|runtime| {
+            remove_runtime(runtime).map(|_| format!("{} ({})", runtime.component, runtime.os))
+        })
+        .collect()
+}
+
+/// Entry point of `rusty-craft runtimes ...`.
+pub fn run_command(args: &[String]) {
+    let installed = match path::get_minecraft_sub_folder("versions") {
+        None => vec![],
+        Some(version_folder) => crate::minecraft_launcher::get_local_versions(&version_folder),
+    };
+    let runtimes = list_installed_runtimes(&installed);
+
+    match (args.get(0).map(|arg| arg.as_str()), args.get(1)) {
+        (None, _) | (Some("list"), _) => {
+            if runtimes.is_empty() {
+                println!("No Java runtime installed.");
+            }
+            for runtime in &runtimes {
+                println!(
+                    "{} ({}) {} - {} - {}",
+                    runtime.component,
+                    runtime.os,
+                    runtime
+                        .version
+                        .clone()
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    format_size(runtime.size),
+                    if runtime.is_used() {
+                        format!("used by {}", runtime.used_by.join(", "))
+                    } else {
+                        "unused".to_string()
+                    }
+                );
+            }
+        }
+        (Some("verify"), component) => {
+            for runtime in runtimes
+                .iter()
+                .filter(|runtime| component.map_or(true, |c| &runtime.component == c))
+            {
+                match verify_runtime(runtime) {
+                    Ok(problems) => {
+                        if problems.is_empty() {
+                            println!("{} ({}): OK", runtime.component, runtime.os);
+                        } else {
+                            println!("{} ({}): broken", runtime.component, runtime.os);
+                            for problem in problems {
+                                println!("  {}", problem);
+                            }
+                        }
+                    }
+                    Err(err) => println!("{} ({}): {}", runtime.component, runtime.os, err),
+                }
+            }
+        }
+        (Some("reinstall"), Some(component)) => match find_runtime(&runtimes, component) {
+            None => println!(
+                "{} isn't installed for {}",
+                component,
+                path::get_os_java_name()
+            ),
+            Some(runtime) => {
+                let (tx, rx) = mpsc::channel();
+                let runtime = runtime.clone();
+                thread::spawn(move || {
+                    reinstall_runtime(&runtime, tx);
+                });
+                for message in rx {
+                    match message {
+                        Message::NewSubStep(name, _, _) => println!("{}", name),
+                        Message::Error(err) => println!("Error: {}", err),
+                        _ => {}
+                    }
+                }
+            }
+        },
+        (Some("remove"), Some(component)) => match find_runtime(&runtimes, component) {
+            None => println!(
+                "{} isn't installed for {}",
+                component,
+                path::get_os_java_name()
+            ),
+            Some(runtime) => match remove_runtime(runtime) {
+                Ok(_) => println!("Removed {} ({})", runtime.component, runtime.os),
+                Err(err) => println!("{}", err),
+            },
+        },
+        (Some("prune"), _) => {
+            let results = remove_unused_runtimes(&installed);
+            if results.is_empty() {
+                println!("No unused runtime.");
+            }
+            for result in results {
+                match result {
+                    Ok(removed) => println!("Removed {}", removed),
+                    Err(err) => println!("{}", err),
+                }
+            }
+        }
+        _ => println!("{}", USAGE),
+    }
+}
+
+pub fn format_size(size: u64) -> String {
+    let mut size = size as f64;
+    let mut unit = 0;
+    let units = ["B", "KiB", "MiB", "GiB"];
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, units[unit])
+}
+
+fn find_runtime<'a>(
+    runtimes: &'a [InstalledRuntime],
+    component: &str,
+) -> Option<&'a InstalledRuntime> {
+    runtimes
+        .iter()
+        .find(|runtime| runtime.component == component && runtime.os == path::get_os_java_name())
+}
+
+// Modded versions usually get their java version from the version they inherit from.
+fn get_java_version(version: &version::Main, installed: &[version::Main]) -> (String, u8) {
+    match &version.java_version {
+        Some(java_version) => (java_version.component.clone(), java_version.major_version),
+        None => match &version.inherits_from {
+            None => (String::from("jre-legacy"), 8),
+            Some(inherits_from) => match installed.iter().find(|v| &v.id == inherits_from) {
+                None => (String::from("jre-legacy"), 8),
+                Some(parent) => get_java_version(parent, installed),
+            },
+        },
+    }
+}
+
+// Mojang's components, for runtimes no installed version uses anymore.
+fn get_component_major_version(component: &str) -> Option<u8> {
+    match component {
+        "jre-legacy" => Some(8),
+        "java-runtime-alpha" => Some(16),
+        "java-runtime-beta" | "java-runtime-gamma" | "java-runtime-gamma-snapshot" => Some(17),
+        "java-runtime-delta" => Some(21),
+        _ => None,
+    }
+}
+
+fn read_version_marker(os_folder: &Path) -> Option<String> {
+    let mut body = String::new();
+
+    match File::open(os_folder.join(".version")) {
+        Ok(mut file) => match file.read_to_string(&mut body) {
+            Ok(_) => Some(body),
+            Err(_) => None,
+        },
+        Err(_) => None,
+    }
+}
+
+fn get_folder_size(folder: &Path) -> u64 {
+    match fs::read_dir(folder) {
+        Ok(read_dir) => read_dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| match entry.path().symlink_metadata() {
+                Ok(meta) if meta.is_dir() => get_folder_size(&entry.path()),
+                Ok(meta) => meta.len(),
+                Err(_) => 0,
+            })
+            .sum(),
+        Err(_) => 0,
+    }
+}
+
+fn get_file_name(path: &Path) -> String {
+    match path.file_name() {
+        None => String::new(),
+        Some(name) => name.to_string_lossy().to_string(),
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn is_executable(_meta: &fs::Metadata) -> bool {
+    true
+}
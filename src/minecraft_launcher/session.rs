@@ -0,0 +1,350 @@
+use crate::minecraft_launcher::crash;
+use crate::minecraft_launcher::crash::CrashReport;
+use crate::minecraft_launcher::game_log;
+use crate::minecraft_launcher::game_log::{GameLog, LogLine};
+use crate::minecraft_launcher::history;
+use crate::minecraft_launcher::history::SessionRecord;
+use crate::minecraft_launcher::known_issues::{Analyzer, Diagnosis};
+use crate::minecraft_launcher::launch;
+use crate::minecraft_launcher::launch::LaunchCommand;
+use crate::minecraft_launcher::monitor::ProcessMonitor;
+
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SessionState {
+    // The pre-launch hook runs, the game starts once it succeeded.
+    PreLaunch,
+    // The hook failed or the game couldn't be started.
+    Failed,
+    Running,
+    // A graceful stop was asked, the game is saving.
+    Stopping,
+    Exited(i32),
+    Crashed(Option<i32>),
+    Killed,
+}
+
+impl SessionState {
+    pub fn get_name(&self) -> String {
+        match self {
+            SessionState::PreLaunch => String::from("Pre-launch"),
+            SessionState::Failed => String::from("Failed"),
+            SessionState::Running => String::from("Running"),
+            SessionState::Stopping => String::from("Stopping"),
+            SessionState::Exited(code) => format!("Exited ({})", code),
+            SessionState::Crashed(Some(code)) => format!("Crashed ({})", code),
+            SessionState::Crashed(None) => String::from("Crashed"),
+            SessionState::Killed => String::from("Killed"),
+        }
+    }
+}
+
+/// A game process started by the launcher, with everything read from it.
+pub struct Session {
+    pub name: String,
+    pub command: LaunchCommand,
+    pub access_token: String,
+    pub state: SessionState,
+    pub log: GameLog,
+    pub crash_reports: Vec<CrashReport>,
+    pub diagnoses: Vec<Diagnosis>,
+    pub messages: Vec<String>,
+    // Set when crash reports were just found, until the view shows them.
+    pub new_crash: bool,
+    pub debug_port: Option<u16>,
+    // Flight Recorder output, written by the JVM when the game exits.
+    pub recording: Option<PathBuf>,
+    pub monitor: ProcessMonitor,
+    child: Option<Child>,
+    started: Instant,
+    launch_time: SystemTime,
+    ended: Option<Instant>,
+    analyzer: Analyzer,
+    analyzed: usize,
+    post_exit_command: Option<String>,
+    record: Option<SessionRecord>,
+    record_id: Option<String>,
+    pre_launch_receiver: Option<Receiver<Result<(), String>>>,
+    hook_receiver: Option<Receiver<Result<(), String>>>,
+}
+
+impl Session {
+    /// Runs the pre-launch hook on its own thread, the game is started by `poll` once it
+    /// succeeded. Nothing is started when the hook fails.
+    pub fn start(
+        name: String,
+        command: LaunchCommand,
+        access_token: String,
+        pre_launch_command: Option<String>,
+        post_exit_command: Option<String>,
+        analyzer: Analyzer,
+        record: SessionRecord,
+    ) -> Result<Session, String> {
+        let mut session = Session {
+            name,
+            command,
+            access_token,
+            state: SessionState::PreLaunch,
+            log: GameLog::new(game_log::DEFAULT_CAPACITY),
+            crash_reports: vec![],
+            diagnoses: vec![],
+            messages: analyzer.errors.clone(),
+            new_crash: false,
+            debug_port: None,
+            recording: None,
+            monitor: ProcessMonitor::new(0),
+            child: None,
+            started: Instant::now(),
+            launch_time: SystemTime::now(),
+            ended: None,
+            analyzer,
+            analyzed: 0,
+            post_exit_command,
+            record: Some(record),
+            record_id: None,
+            pre_launch_receiver: None,
+            hook_receiver: None,
+        };
+
+        match pre_launch_command {
+            None => session.spawn()?,
+            Some(pre_launch_command) => {
+                let (tx, rx) = channel();
+                session.pre_launch_receiver = Some(rx);
+                session
+                    .messages
+                    .push(format!("Running pre-launch command {}", pre_launch_command));
+
+                let command = session.command.clone();
+                thread::spawn(move || {
+                    tx.send(
+                        launch::run_hook(&pre_launch_command, &command, None)
+                            .map_err(|err| format!("Pre-launch command failed: {}", err)),
+                    )
+                    .unwrap_or(());
+                });
+            }
+        }
+
+        Ok(session)
+    }
+
+    fn spawn(&mut self) -> Result<(), String> {
+        let mut child = launch::main(&self.command)?;
+        self.log.attach(child.stdout.take(), child.stderr.take());
+
+        // The game still runs without history, the error is only shown.
+        if let Some(record) = self.record.take() {
+            match history::add_record(&record) {
+                Ok(_) => self.record_id = Some(record.id),
+                Err(err) => self.messages.push(err),
+            }
+        }
+
+        self.state = SessionState::Running;
+        self.monitor = ProcessMonitor::new(child.id());
+        self.child = Some(child);
+        self.started = Instant::now();
+        self.launch_time = SystemTime::now();
+        Ok(())
+    }
+
+    // The game never started, so there is nothing to record or clean after.
+    fn fail(&mut self, err: String) {
+        self.messages.push(err);
+        self.state = SessionState::Failed;
+        self.ended = Some(Instant::now());
+        self.post_exit_command = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.child.is_some() || self.state == SessionState::PreLaunch
+    }
+
+    /// Whether the game or its post-exit command is still going.
+    pub fn is_active(&self) -> bool {
+        self.is_running() || self.hook_receiver.is_some()
+    }
+
+    pub fn get_uptime(&self) -> Duration {
+        self.ended.unwrap_or_else(Instant::now) - self.started
+    }
+
+    /// Reads the new output and checks whether the game exited, returns the dropped log lines.
+    pub fn poll(&mut self) -> Vec<LogLine> {
+        if let Some(receiver) = &self.pre_launch_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.pre_launch_receiver = None;
+                match result.and_then(|_| self.spawn()) {
+                    Ok(_) => self.messages.push(String::from("Ran pre-launch command")),
+                    Err(err) => self.fail(err),
+                }
+            }
+        }
+
+        let dropped = self.log.poll();
+
+        let new_lines = (self.log.received - self.analyzed).min(self.log.lines.len());
+        let skipped = self.log.lines.len() - new_lines;
+        let new_lines: Vec<String> = self
+            .log
+            .lines
+            .iter()
+            .skip(skipped)
+            .map(|line| match &line.throwable {
+                None => line.text.clone(),
+                Some(throwable) => format!("{}\n{}", line.text, throwable),
+            })
+            .collect();
+        for line in new_lines {
+            self.diagnose(&line);
+        }
+        self.analyzed = self.log.received;
+
+        let exit_status = match self.child.as_mut() {
+            None => None,
+            Some(child) => child.try_wait().unwrap_or(None),
+        };
+        if let Some(exit_status) = exit_status {
+            self.child = None;
+            self.on_exit(exit_status.code());
+        } else if self.child.is_some() {
+            self.monitor.update();
+        }
+
+        if let Some(receiver) = &self.hook_receiver {
+            if let Ok(result) = receiver.try_recv() {
+                self.messages.push(match result {
+                    Ok(_) => String::from("Ran post-exit command"),
+                    Err(err) => format!("Post-exit command failed: {}", err),
+                });
+                self.hook_receiver = None;
+            }
+        }
+
+        dropped
+    }
+
+    /// Asks the game to close, giving it the time to save the world.
+    pub fn stop(&mut self) -> Result<(), String> {
+        if self.state == SessionState::PreLaunch {
+            self.cancel();
+            return Ok(());
+        }
+
+        let child = match &self.child {
+            None => return Ok(()),
+            Some(child) => child,
+        };
+
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("taskkill");
+            command.arg("/PID").arg(child.id().to_string());
+            command
+        } else {
+            let mut command = Command::new("kill");
+            command.arg("-TERM").arg(child.id().to_string());
+            command
+        };
+
+        match command.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+            Ok(status) if status.success() => {
+                self.state = SessionState::Stopping;
+                Ok(())
+            }
+            Ok(status) => Err(format!("Unable to stop {}: {}", self.name, status)),
+            Err(err) => Err(format!("Unable to stop {}: {}", self.name, err)),
+        }
+    }
+
+    pub fn kill(&mut self) -> Result<(), String> {
+        if self.state == SessionState::PreLaunch {
+            self.cancel();
+            return Ok(());
+        }
+
+        match self.child.as_mut() {
+            None => Ok(()),
+            Some(child) => match child.kill() {
+                Ok(_) => {
+                    self.state = SessionState::Killed;
+                    Ok(())
+                }
+                Err(err) => Err(format!("Unable to kill {}: {}", self.name, err)),
+            },
+        }
+    }
+
+    // The hook is left to finish, only the game isn't started.
+    fn cancel(&mut self) {
+        self.pre_launch_receiver = None;
+        self.post_exit_command = None;
+        self.state = SessionState::Killed;
+        self.ended = Some(Instant::now());
+        self.messages.push(String::from("Launch cancelled"));
+    }
+
+    fn diagnose(&mut self, text: &str) {
+        for diagnosis in self.analyzer.analyze(text) {
+            if !self.diagnoses.contains(&diagnosis) {
+                self.diagnoses.push(diagnosis);
+            }
+        }
+    }
+
+    fn on_exit(&mut self, exit_code: Option<i32>) {
+        self.ended = Some(Instant::now());
+
+        self.crash_reports = crash::find_new_reports(&self.command.game_dir, self.launch_time);
+        for report in self.crash_reports.clone() {
+            self.diagnose(&report.content);
+        }
+
+        let stopped = matches!(self.state, SessionState::Stopping | SessionState::Killed);
+        self.state = match exit_code {
+            _ if !self.crash_reports.is_empty() => SessionState::Crashed(exit_code),
+            // Signals asked by the user aren't crashes.
+            None if stopped => SessionState::Killed,
+            Some(code) if code == 0 || stopped => SessionState::Exited(code),
+            _ => SessionState::Crashed(exit_code),
+        };
+
+        self.messages.push(match exit_code {
+            Some(exit_code) => format!("Minecraft exited with code {}", exit_code),
+            None => String::from("Minecraft was killed"),
+        });
+        if let Some(recording) = &self.recording {
+            self.messages.push(match recording.is_file() {
+                true => format!("Flight recording saved to {}", recording.display()),
+                false => format!("No flight recording was written to {}", recording.display()),
+            });
+        }
+        if let Some(report) = self.crash_reports.first() {
+            self.messages
+                .push(format!("Minecraft crashed, see {}", report.path.display()));
+            self.new_crash = true;
+        }
+
+        if let Some(record_id) = self.record_id.take() {
+            if let Err(err) = history::finish_record(&record_id, exit_code) {
+                self.messages.push(err);
+            }
+        }
+
+        if let Some(post_exit_command) = self.post_exit_command.take() {
+            let (tx, rx) = channel();
+            self.hook_receiver = Some(rx);
+
+            let command = self.command.clone();
+            thread::spawn(move || {
+                tx.send(launch::run_hook(&post_exit_command, &command, exit_code))
+                    .unwrap_or(());
+            });
+        }
+    }
+}
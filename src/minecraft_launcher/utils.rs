@@ -1,8 +1,11 @@
 use reqwest::blocking::get as get_url;
 use serde_derive::Deserialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
 
 use std::fs::File;
-use std::io::Read;
+use std::io;
+use std::io::{Read, Write};
 use std::path::Path;
 
 pub fn get_body_from_url_else_from_file(url: &str, path: &Path) -> Option<String> {
@@ -31,6 +34,30 @@ pub fn get_body_from_url_else_from_file(url: &str, path: &Path) -> Option<String
     }
 }
 
+pub fn get_file_sha1(path: &Path) -> Result<String, String> {
+    let mut hasher = Sha1::new();
+    hash_file(path, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn get_file_sha256(path: &Path) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    hash_file(path, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_file(path: &Path, hasher: &mut dyn Write) -> Result<(), String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(err) => return Err(format!("Unable to open {}: {}", path.display(), err)),
+    };
+
+    match io::copy(&mut file, hasher) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("Unable to read {}: {}", path.display(), err)),
+    }
+}
+
 #[derive(Deserialize, PartialEq)]
 pub struct Metadata {
     #[serde(rename = "groupId")]
@@ -64,3 +91,60 @@ pub struct MavenVersion {
     #[serde(rename = "version", default)]
     pub versions: Vec<String>,
 }
+
+/// Asks the terminal to put `text` in the clipboard with an OSC 52 sequence, which also works
+/// through SSH and tmux.
+pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    let mut stdout = std::io::stdout();
+
+    match write!(stdout, "\x1b]52;c;{}\x07", encode_base64(text.as_bytes()))
+        .and_then(|_| stdout.flush())
+    {
+        Ok(_) => Ok(()),
+        Err(err) => Err(format!("Unable to copy to the clipboard: {}", err)),
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_padding() {
+        let cases = [
+            ("", ""),
+            ("M", "TQ=="),
+            ("Ma", "TWE="),
+            ("Man", "TWFu"),
+            ("Many", "TWFueQ=="),
+        ];
+
+        for (i, (input, expected)) in cases.iter().enumerate() {
+            assert_eq!(encode_base64(input.as_bytes()), *expected, "case {}", i);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::minecraft_launcher::arguments;
+use crate::minecraft_launcher::arguments::{LaunchOptions, Platform};
+use crate::minecraft_launcher::manifest::version;
+use crate::minecraft_launcher::manifest::version::RuleAction;
+use crate::minecraft_launcher::{path, utils};
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub struct MissingFile {
+    pub description: String,
+    pub path: PathBuf,
+}
+
+/// Everything the game needs to start that isn't on disk, checked before spawning Java.
+pub fn find_missing_files(
+    version: &version::Main,
+    options: &LaunchOptions,
+    java_path: &Path,
+) -> Vec<MissingFile> {
+    let mut missing = vec![];
+
+    if !java_path.is_file() {
+        missing.push(MissingFile {
+            description: String::from("Java executable"),
+            path: java_path.to_path_buf(),
+        });
+    }
+
+    for entry in options
+        .classpath
+        .split(arguments::get_classpath_separator())
+        .filter(|entry| !entry.is_empty())
+    {
+        let entry_path = PathBuf::from(entry);
+        if !entry_path.is_file() {
+            missing.push(MissingFile {
+                description: if entry == options.primary_jar {
+                    String::from("Client jar")
+                } else {
+                    String::from("Library")
+                },
+                path: entry_path,
+            });
+        }
+    }
+
+    // Replacements from the library overrides have no size, only the SHA-1 of their repository.
+    for artifact in version
+        .libraries
+        .iter()
+        .filter_map(|library| library.downloads.as_ref()?.artifact.as_ref())
+        .filter(|artifact| artifact.size == 0 && !artifact.sha1.is_empty())
+    {
+        if let Some(library_path) = path::get_library_path(&artifact.path) {
+            let damaged = library_path.is_file()
+                && utils::get_file_sha1(&library_path).map_or(true, |sha1| sha1 != artifact.sha1);
+            if damaged {
+                missing.push(MissingFile {
+                    description: String::from("Damaged library"),
+                    path: library_path,
+                });
+            }
+        }
+    }
+
+    // The folder is created with the launch options, only its content tells whether the
+    // natives were extracted.
+    let natives_directory = PathBuf::from(&options.natives_directory);
+    if has_natives(version) && is_empty_dir(&natives_directory) {
+        missing.push(MissingFile {
+            description: String::from("Extracted natives"),
+            path: natives_directory,
+        });
+    }
+
+    if let Some(indexes) = path::get_assets_folder("indexes") {
+        let index_path = indexes.join(format!("{}.json", options.assets_index));
+        if !index_path.is_file() {
+            missing.push(MissingFile {
+                description: String::from("Asset index"),
+                path: index_path,
+            });
+        }
+    }
+
+    // The patched config of the Log4Shell mitigation was written before the arguments were built.
+    let replaced_config = options
+        .log4j_mitigation
+        .as_ref()
+        .map_or(false, |(mitigation, _)| mitigation.replaces_config());
+    let log_config = version
+        .logging
+        .as_ref()
+        .and_then(|logging| logging.client.as_ref())
+        .filter(|_| !replaced_config);
+    if let (Some(log_config), Some(log_configs)) =
+        (log_config, path::get_assets_folder("log_configs"))
+    {
+        let config_path = log_configs.join(&log_config.file.id);
+        if !config_path.is_file() {
+            missing.push(MissingFile {
+                description: String::from("Log config"),
+                path: config_path,
+            });
+        }
+    }
+
+    missing
+}
+
+// Natives are only extracted from libraries with a classifier for this OS, since 1.19 they
+// are regular libraries that LWJGL extracts itself.
+fn has_natives(version: &version::Main) -> bool {
+    let os = arguments::get_os().to_str();
+    let platform = Platform::current();
+
+    version.libraries.iter().any(|library| {
+        let allowed = match &library.rules {
+            None => true,
+            Some(rules) => matches!(
+                arguments::match_rules(rules, &HashSet::new(), &platform),
+                RuleAction::Allow
+            ),
+        };
+        allowed
+            && library
+                .natives
+                .as_ref()
+                .map_or(false, |natives| natives.contains_key(&os))
+    })
+}
+
+fn is_empty_dir(dir: &Path) -> bool {
+    match fs::read_dir(dir) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => true,
+    }
+}